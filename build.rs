@@ -1,7 +1,9 @@
-use std::env;
+use std::{env, process::Command};
 
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-check-cfg=cfg(is_thumb)");
+    println!("cargo:rustc-check-cfg=cfg(proc_macro_span)");
 
     // The most reliable way is to check for the thumb-mode feature in
     // CARGO_CFG_TARGET_FEATURE but this is only available on nightly. As a
@@ -11,4 +13,23 @@ fn main() {
     if arch == "arm" && target.starts_with("thumb") {
         println!("cargo:rustc-cfg=is_thumb");
     }
+
+    // The `debug_lines` option needs the source locations of spans, which are
+    // only available to proc macros since Rust 1.88. This is only used to
+    // gate the tests of that option.
+    if rustc_minor_version().map_or(false, |minor| minor >= 88) {
+        println!("cargo:rustc-cfg=proc_macro_span");
+    }
+}
+
+/// Returns the minor version of the rustc that is building this crate.
+fn rustc_minor_version() -> Option<u32> {
+    let rustc = env::var_os("RUSTC")?;
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+    let mut pieces = version.split('.');
+    if pieces.next() != Some("rustc 1") {
+        return None;
+    }
+    pieces.next()?.parse().ok()
 }
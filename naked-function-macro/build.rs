@@ -0,0 +1,43 @@
+use std::{env, process::Command};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-check-cfg=cfg(unsafe_extern_blocks)");
+    println!("cargo:rustc-check-cfg=cfg(native_naked_functions)");
+    println!("cargo:rustc-check-cfg=cfg(proc_macro_span)");
+
+    // Edition 2024 requires extern blocks to be marked as `unsafe`, but this
+    // syntax is only accepted since Rust 1.82.
+    let minor = rustc_minor_version().unwrap_or(0);
+    if minor >= 82 {
+        println!("cargo:rustc-cfg=unsafe_extern_blocks");
+    }
+
+    // Naked functions are natively supported since Rust 1.88, in which case
+    // they are used instead of lowering the function to `global_asm!`.
+    if minor >= 88 {
+        println!("cargo:rustc-cfg=native_naked_functions");
+    }
+
+    // The source locations of spans are available to proc macros since
+    // Rust 1.88.
+    if minor >= 88 {
+        println!("cargo:rustc-cfg=proc_macro_span");
+    }
+}
+
+/// Returns the minor version of the rustc that is building this crate.
+///
+/// Since proc macros are always built by the same compiler as the crate that
+/// uses them, this is also the version of the compiler that will consume the
+/// code generated by the macro.
+fn rustc_minor_version() -> Option<u32> {
+    let rustc = env::var_os("RUSTC")?;
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+    let mut pieces = version.split('.');
+    if pieces.next() != Some("rustc 1") {
+        return None;
+    }
+    pieces.next()?.parse().ok()
+}
@@ -0,0 +1,88 @@
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Meta, Path, Result, Token,
+};
+
+/// Options passed as arguments to the `#[naked]` attribute.
+#[derive(Default)]
+pub struct NakedArgs {
+    /// Target-specific options such as `riscv::norelax` or `arm::pad(8)`.
+    ///
+    /// These are validated by the helper macros in the `naked-function` crate
+    /// since the proc macro doesn't know which target it is compiling for.
+    pub target_options: Vec<Meta>,
+
+    /// Whether unwind info should be emitted for the function, as set by the
+    /// `unwind_tables` and `no_unwind_tables` options.
+    ///
+    /// If neither is specified then call frame information is emitted for
+    /// functions with an `-unwind` ABI.
+    pub unwind_tables: Option<bool>,
+
+    /// Whether the function is an entry point which terminates the frame
+    /// chain, as set by the `entry_point` option.
+    pub entry_point: bool,
+
+    /// Whether line number information should be emitted for the function,
+    /// as set by the `debug_lines` option.
+    pub debug_lines: bool,
+}
+
+impl NakedArgs {
+    /// Returns whether the given target-specific option was specified.
+    pub fn has_target_option(&self, target: &str, name: &str) -> bool {
+        self.target_options
+            .iter()
+            .map(Meta::path)
+            .any(|path| path.segments[0].ident == target && path.segments[1].ident == name)
+    }
+}
+
+/// Returns whether the path names a target-specific option, which is of the
+/// form `target::option`.
+fn is_target_option(path: &Path) -> bool {
+    path.leading_colon.is_none() && path.segments.len() == 2
+}
+
+impl Parse for NakedArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut args = NakedArgs::default();
+        let metas: Punctuated<Meta, Token![,]> = Punctuated::parse_terminated(input)?;
+        for meta in metas {
+            match meta {
+                Meta::Path(path)
+                    if path.is_ident("unwind_tables") || path.is_ident("no_unwind_tables") =>
+                {
+                    if args.unwind_tables.is_some() {
+                        bail!(path, "unwind tables option specified multiple times");
+                    }
+                    args.unwind_tables = Some(path.is_ident("unwind_tables"));
+                    if args.entry_point && args.unwind_tables == Some(false) {
+                        bail!(path, "`no_unwind_tables` can't be used with `entry_point`");
+                    }
+                }
+                Meta::Path(path) if path.is_ident("entry_point") => {
+                    if args.entry_point {
+                        bail!(path, "entry_point option specified multiple times");
+                    }
+                    if args.unwind_tables == Some(false) {
+                        bail!(path, "`entry_point` can't be used with `no_unwind_tables`");
+                    }
+                    args.entry_point = true;
+                }
+                Meta::Path(path) if path.is_ident("debug_lines") => {
+                    if args.debug_lines {
+                        bail!(path, "debug_lines option specified multiple times");
+                    }
+                    args.debug_lines = true;
+                }
+                Meta::Path(_) | Meta::List(_) if is_target_option(meta.path()) => {
+                    args.target_options.push(meta);
+                }
+                _ => bail!(meta, "unknown #[naked] option"),
+            }
+        }
+        Ok(args)
+    }
+}
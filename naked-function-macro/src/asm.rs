@@ -1,11 +1,11 @@
-use quote::ToTokens;
+use quote::{format_ident, quote, ToTokens};
 use syn::{
     ext::IdentExt,
     parenthesized,
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
     token::Paren,
-    Expr, Ident, ItemFn, Result, Stmt, Token,
+    Expr, ExprLit, Ident, ItemFn, Lit, LitStr, Path, PathArguments, Result, Stmt, Token,
 };
 
 pub mod kw {
@@ -18,7 +18,22 @@ pub mod kw {
     syn::custom_keyword!(clobber_abi);
 }
 
+/// Modifiers which may be applied to `sym` operands as `sym(modifier) path`.
+///
+/// These change how the symbol is referenced in the template, which is
+/// implemented by the `__asm_sym_<modifier>!` helper macro for each target.
+const SYM_MODIFIERS: &[&str] = &["call", "tls", "tls_ie", "pm", "gs", "got", "gotoff"];
+
+/// Modifiers which expand to a thread-local access sequence, whose format spec
+/// selects the register which receives the address of the variable.
+const TLS_SYM_MODIFIERS: &[&str] = &["tls", "tls_ie"];
+
+/// Modifiers which reference a symbol relative to the GOT, which requires the
+/// helper to load the program counter on 32-bit x86.
+const GOT_SYM_MODIFIERS: &[&str] = &["got", "gotoff", "tls_ie"];
+
 /// Representation of one argument of the `asm!` macro.
+#[derive(Clone)]
 pub enum AsmOperand {
     Template(Expr),
     Const {
@@ -29,6 +44,7 @@ pub enum AsmOperand {
     Sym {
         name: Option<(Ident, Token![=])>,
         token: kw::sym,
+        modifier: Option<Ident>,
         expr: Expr,
     },
     Options {
@@ -44,7 +60,23 @@ impl Parse for AsmOperand {
             let token = input.parse::<kw::options>()?;
             let content;
             let paren_token = parenthesized!(content in input);
-            let options = content.parse_terminated(Ident::parse, Token![,])?;
+            let mut options = Punctuated::new();
+            for option in content.parse_terminated(Ident::parse, Token![,])? {
+                // Naked functions can never fall through the end of the asm
+                // block, so `noreturn` is implied. Older nightly versions of
+                // `#[naked]` required it so it is accepted and ignored.
+                if option == "noreturn" {
+                    continue;
+                }
+                if option != "raw" && option != "att_syntax" {
+                    bail!(
+                        &option,
+                        "the `{}` option cannot be used in naked functions",
+                        option
+                    );
+                }
+                options.push(option);
+            }
             return Ok(Self::Options {
                 token,
                 paren_token,
@@ -61,8 +93,24 @@ impl Parse for AsmOperand {
 
         if input.peek(kw::sym) {
             let token = input.parse()?;
+            let modifier = if input.peek(Paren) {
+                let content;
+                parenthesized!(content in input);
+                let modifier: Ident = content.parse()?;
+                if !SYM_MODIFIERS.contains(&&*modifier.to_string()) {
+                    bail!(&modifier, "unknown `sym` operand modifier `{}`", modifier);
+                }
+                Some(modifier)
+            } else {
+                None
+            };
             let expr = input.parse()?;
-            return Ok(Self::Sym { name, token, expr });
+            return Ok(Self::Sym {
+                name,
+                token,
+                modifier,
+                expr,
+            });
         }
 
         if input.peek(Token![const]) {
@@ -111,7 +159,13 @@ impl ToTokens for AsmOperand {
                 token.to_tokens(tokens);
                 expr.to_tokens(tokens);
             }
-            AsmOperand::Sym { name, token, expr } => {
+            // Modifiers are applied to the template by `apply_sym_modifiers`.
+            AsmOperand::Sym {
+                name,
+                token,
+                modifier: _,
+                expr,
+            } => {
                 if let Some((ident, token)) = name {
                     ident.to_tokens(tokens);
                     token.to_tokens(tokens);
@@ -133,6 +187,29 @@ impl ToTokens for AsmOperand {
     }
 }
 
+/// Checks whether a macro path refers to `asm!` or `naked_asm!`, either
+/// directly or through its full path in `core::arch` or `std::arch`.
+fn is_asm_path(path: &Path) -> bool {
+    if path
+        .segments
+        .iter()
+        .any(|segment| !matches!(segment.arguments, PathArguments::None))
+    {
+        return false;
+    }
+    let segments: Vec<String> = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    match segments[..] {
+        ["asm" | "naked_asm"] => path.leading_colon.is_none(),
+        ["core" | "std", "arch", "asm" | "naked_asm"] => true,
+        _ => false,
+    }
+}
+
 /// Extracts the `AsmOperand`s from the `asm!` in the body of the function.
 pub fn extract_asm(func: &ItemFn) -> Result<Punctuated<AsmOperand, Token![,]>> {
     if func.block.stmts.len() != 1 {
@@ -149,11 +226,328 @@ pub fn extract_asm(func: &ItemFn) -> Result<Punctuated<AsmOperand, Token![,]>> {
             "naked functions may only contain a single asm! statement"
         ),
     };
-    if !attrs.is_empty() || !mac.path.is_ident("asm") {
+    if !attrs.is_empty() || !is_asm_path(&mac.path) {
         bail!(
             func,
             "naked functions may only contain a single asm! statement"
         );
     }
-    mac.parse_body_with(Punctuated::parse_terminated)
+    let operands: Punctuated<AsmOperand, Token![,]> =
+        mac.parse_body_with(Punctuated::parse_terminated)?;
+
+    // Ignoring `noreturn` may leave behind an empty `options()` which we can
+    // just drop entirely.
+    Ok(operands
+        .into_iter()
+        .filter(|op| !matches!(op, AsmOperand::Options { options, .. } if options.is_empty()))
+        .collect())
+}
+
+/// Splits a template string into literal text and the contents of its
+/// `{...}` placeholders.
+///
+/// Escaped braces are kept as-is in the literal text since the template is
+/// still a format string.
+fn split_placeholders(template: &str) -> Option<Vec<(String, Option<String>)>> {
+    let mut pieces = vec![];
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push_str("{{");
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push_str("}}");
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next()? {
+                        '}' => break,
+                        c => placeholder.push(c),
+                    }
+                }
+                pieces.push((std::mem::take(&mut text), Some(placeholder)));
+            }
+            '}' => return None,
+            c => text.push(c),
+        }
+    }
+    pieces.push((text, None));
+    Some(pieces)
+}
+
+/// Applies the modifiers of `sym(modifier)` operands to the templates.
+///
+/// Each placeholder referring to such an operand is wrapped in a call to
+/// `__asm_sym_<modifier>!`, which emits the target-specific form of the symbol
+/// reference. Since this splits the templates, all implicit placeholders are
+/// replaced with explicit operand indices.
+pub fn apply_sym_modifiers(asm: &mut Punctuated<AsmOperand, Token![,]>) -> Result<()> {
+    let operands: Vec<(Option<String>, Option<Ident>)> = asm
+        .iter()
+        .filter_map(|op| match op {
+            AsmOperand::Const { name, .. } => {
+                Some((name.as_ref().map(|(ident, _)| ident.to_string()), None))
+            }
+            AsmOperand::Sym { name, modifier, .. } => Some((
+                name.as_ref().map(|(ident, _)| ident.to_string()),
+                modifier.clone(),
+            )),
+            AsmOperand::Template(_) | AsmOperand::Options { .. } => None,
+        })
+        .collect();
+    let modifier = match operands.iter().find_map(|(_, modifier)| modifier.as_ref()) {
+        Some(modifier) => modifier,
+        None => return Ok(()),
+    };
+
+    for op in asm.iter() {
+        if let AsmOperand::Options { options, .. } = op {
+            if options.iter().any(|option| option == "raw") {
+                bail!(
+                    modifier,
+                    "`sym` operand modifiers cannot be used with the `raw` option"
+                );
+            }
+            // The thread-local access sequences use Intel syntax on x86.
+            if options.iter().any(|option| option == "att_syntax") {
+                if let Some(tls) = operands
+                    .iter()
+                    .filter_map(|(_, modifier)| modifier.as_ref())
+                    .find(|modifier| TLS_SYM_MODIFIERS.contains(&&*modifier.to_string()))
+                {
+                    bail!(
+                        tls,
+                        "`sym({})` operands cannot be used with the `att_syntax` option",
+                        tls
+                    );
+                }
+            }
+        }
+    }
+
+    let mut next_implicit = 0;
+    for op in asm.iter_mut() {
+        let template = match op {
+            AsmOperand::Template(template) => template,
+            _ => continue,
+        };
+        let lit = match template {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            }) => lit.clone(),
+            _ => bail!(
+                template,
+                "templates must be string literals when using `sym` operand modifiers"
+            ),
+        };
+        let pieces = match split_placeholders(&lit.value()) {
+            Some(pieces) => pieces,
+            None => bail!(lit, "invalid format string"),
+        };
+
+        let mut parts = vec![];
+        let mut text = String::new();
+        for (literal, placeholder) in pieces {
+            text.push_str(&literal);
+            let placeholder = match placeholder {
+                Some(placeholder) => placeholder,
+                None => continue,
+            };
+            let (arg, spec) = match placeholder.split_once(':') {
+                Some((arg, spec)) => (arg, Some(spec)),
+                None => (&*placeholder, None),
+            };
+            let arg = if arg.is_empty() {
+                next_implicit += 1;
+                (next_implicit - 1).to_string()
+            } else {
+                arg.to_string()
+            };
+
+            // Find the operand that the placeholder refers to, either by index
+            // or by name.
+            let operand = match arg.parse::<usize>() {
+                Ok(index) => operands.get(index),
+                Err(_) => operands
+                    .iter()
+                    .find(|(name, _)| name.as_deref() == Some(&*arg)),
+            };
+            let modifier = operand.and_then(|(_, modifier)| modifier.as_ref());
+            match modifier {
+                Some(modifier) => {
+                    parts.push(
+                        LitStr::new(&std::mem::take(&mut text), lit.span()).into_token_stream(),
+                    );
+                    let placeholder = LitStr::new(&format!("{{{}}}", arg), lit.span());
+                    let spec = match spec {
+                        Some(spec) if TLS_SYM_MODIFIERS.contains(&&*modifier.to_string()) => {
+                            let spec = LitStr::new(spec, lit.span());
+                            Some(quote!(, #spec))
+                        }
+                        Some(_) => bail!(
+                            lit,
+                            "format specs cannot be used with `sym({})` operands",
+                            modifier
+                        ),
+                        None => None,
+                    };
+                    let helper = format_ident!("__asm_sym_{}", modifier, span = modifier.span());
+                    parts.push(quote!(::naked_function::#helper!(#placeholder #spec)));
+                }
+                None => match spec {
+                    Some(spec) => text.push_str(&format!("{{{}:{}}}", arg, spec)),
+                    None => text.push_str(&format!("{{{}}}", arg)),
+                },
+            }
+        }
+        parts.push(LitStr::new(&text, lit.span()).into_token_stream());
+
+        *template = if parts.len() == 1 {
+            syn::parse2(parts.pop().unwrap())?
+        } else {
+            syn::parse2(quote!(::core::concat!(#(#parts),*)))?
+        };
+    }
+
+    // Emit the PC thunk used to compute the GOT address. It is placed in its
+    // own section so that it doesn't affect the function body.
+    if operands.iter().any(|(_, modifier)| {
+        modifier.as_ref().map_or(false, |modifier| {
+            GOT_SYM_MODIFIERS.contains(&&*modifier.to_string())
+        })
+    }) {
+        let last_template = asm
+            .iter()
+            .rposition(|op| matches!(op, AsmOperand::Template(_)))
+            .unwrap();
+        asm.insert(
+            last_template + 1,
+            AsmOperand::Template(syn::parse2(quote!(::naked_function::__asm_pc_thunk!()))?),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse::Parser;
+
+    /// Applies the `sym` modifiers to the operands of an `asm!` invocation and
+    /// returns the resulting templates.
+    fn templates(tokens: proc_macro2::TokenStream) -> Result<Vec<String>> {
+        let mut asm = Punctuated::<AsmOperand, Token![,]>::parse_terminated.parse2(tokens)?;
+        apply_sym_modifiers(&mut asm)?;
+        Ok(asm
+            .iter()
+            .filter_map(|op| match op {
+                AsmOperand::Template(template) => Some(template.to_token_stream().to_string()),
+                _ => None,
+            })
+            .collect())
+    }
+
+    #[test]
+    fn split_placeholders() {
+        assert_eq!(
+            super::split_placeholders("a {} b {0:x} {name}").unwrap(),
+            [
+                ("a ".to_string(), Some(String::new())),
+                (" b ".to_string(), Some("0:x".to_string())),
+                (" ".to_string(), Some("name".to_string())),
+                (String::new(), None),
+            ]
+        );
+        assert_eq!(
+            super::split_placeholders("{{}} {{x}}").unwrap(),
+            [("{{}} {{x}}".to_string(), None)]
+        );
+        assert!(super::split_placeholders("{").is_none());
+        assert!(super::split_placeholders("}").is_none());
+    }
+
+    #[test]
+    fn unmodified_templates() {
+        assert_eq!(
+            templates(quote!("call {}", "{}", sym foo, const 1)).unwrap(),
+            [r#""call {}""#, r#""{}""#]
+        );
+    }
+
+    #[test]
+    fn implicit_placeholders() {
+        assert_eq!(
+            templates(quote!("ldi r30, lo8({})", "ldi r31, hi8({})", sym(pm) foo, sym bar))
+                .unwrap(),
+            [
+                r#":: core :: concat ! ("ldi r30, lo8(" , :: naked_function :: __asm_sym_pm ! ("{0}") , ")")"#,
+                r#""ldi r31, hi8({1})""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn explicit_placeholders() {
+        assert_eq!(
+            templates(quote!(
+                "{x} {0} {2:y} {x}",
+                sym foo,
+                x = sym(gs) bar,
+                const 1,
+            ))
+            .unwrap(),
+            [concat!(
+                r#":: core :: concat ! ("" , :: naked_function :: __asm_sym_gs ! ("{x}") , "#,
+                r#"" {0} {2:y} " , :: naked_function :: __asm_sym_gs ! ("{x}") , "")"#,
+            )]
+        );
+        assert_eq!(
+            templates(quote!("{1} {0}", sym foo, sym(pm) bar)).unwrap(),
+            [concat!(
+                r#":: core :: concat ! ("" , :: naked_function :: __asm_sym_pm ! ("{1}") , "#,
+                r#"" {0}")"#,
+            )]
+        );
+    }
+
+    #[test]
+    fn escaped_braces() {
+        assert_eq!(
+            templates(quote!("{{{}}} {{}}", sym(pm) foo)).unwrap(),
+            [concat!(
+                r#":: core :: concat ! ("{{" , :: naked_function :: __asm_sym_pm ! ("{0}") , "#,
+                r#""}} {{}}")"#,
+            )]
+        );
+    }
+
+    #[test]
+    fn format_specs() {
+        assert_eq!(
+            templates(quote!("{:rdx}", sym(tls) foo)).unwrap(),
+            [
+                r#":: core :: concat ! ("" , :: naked_function :: __asm_sym_tls ! ("{0}" , "rdx") , "")"#
+            ]
+        );
+        assert_eq!(
+            templates(quote!("{:x17}", sym(tls_ie) foo)).unwrap(),
+            [
+                r#":: core :: concat ! ("" , :: naked_function :: __asm_sym_tls_ie ! ("{0}" , "x17") , "")"#,
+                ":: naked_function :: __asm_pc_thunk ! ()",
+            ]
+        );
+        assert!(templates(quote!("{:x}", sym(pm) foo)).is_err());
+    }
+
+    #[test]
+    fn invalid_templates() {
+        assert!(templates(quote!("{", sym(pm) foo)).is_err());
+        assert!(templates(quote!("}", sym(pm) foo)).is_err());
+        assert!(templates(quote!("{}", sym(pm) foo, options(raw))).is_err());
+    }
 }
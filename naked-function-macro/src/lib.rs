@@ -6,7 +6,7 @@ use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 
 use quote::ToTokens;
-use syn::{parse::Nothing, parse_macro_input};
+use syn::parse_macro_input;
 
 macro_rules! bail {
     ($span:expr, $($tt:tt)*) => {
@@ -14,7 +14,9 @@ macro_rules! bail {
     };
 }
 
+mod args;
 mod asm;
+mod mangle;
 mod naked;
 
 /// An attribute to define a function written entirely in assembly.
@@ -24,6 +26,10 @@ mod naked;
 /// epilogue. This means that the assembly code is responsible for including
 /// the necessary instructions to return from a function.
 ///
+/// The macro may also be written as `naked_asm!` or by its full path
+/// (`core::arch::asm!`, `std::arch::naked_asm!`, etc.) so that the same code
+/// can be used with native naked functions.
+///
 /// The primary use of naked function is to implement functions that use a
 /// custom calling convention that is not directly supported by rustc. Examples
 /// include hardware exception handlers, functions called from assembly code
@@ -43,7 +49,6 @@ mod naked;
 ///     );
 /// }
 ///
-/// #[test]
 /// fn main() {
 ///     let ret = unsafe { add(1, 2) };
 ///     assert_eq!(ret, 3);
@@ -57,9 +62,95 @@ mod naked;
 /// - `clobber_abi` cannot be used.
 /// - Only the `raw` and `att_syntax` options can be used.
 ///
+/// The `noreturn` option, which was required by the nightly-only `#[naked]`
+/// attribute, is implied for naked functions and is silently ignored.
+///
 /// These are the same set of operands accepted by `global_asm!`, which this
 /// attribute lowers the functions into.
 ///
+/// ## `sym` operand modifiers
+///
+/// A `sym` operand may be written as `sym(modifier) path` to reference the
+/// symbol in a target-specific form. Templates must be string literals when
+/// modifiers are used, and the `raw` option can't be used.
+///
+/// - `sym(call)`: References a function as the target of a call instruction,
+///   going through the PLT where required so that the function may be defined
+///   in a shared library (`symbol@PLT` on x86, x86_64, s390x and 32-bit
+///   PowerPC ELF targets). On other targets the symbol is referenced directly:
+///   the linker inserts PLT entries for direct calls on AArch64, ARM, RISC-V
+///   and LoongArch, and calls to functions imported from DLLs go through the
+///   thunks in the import library on COFF targets. On 32-bit x86, `ebx` must
+///   hold the address of the GOT when calling through the PLT in
+///   position-independent code.
+/// - `sym(tls)`: References a `#[thread_local]` static. The placeholder
+///   expands to a sequence of instructions which computes the address of the
+///   variable for the current thread into a register, so it should be used as
+///   a template on its own. The register can be given as the format spec of
+///   the placeholder (`"{:rdx}"`) and defaults to the return value register
+///   (`rax`, `eax`, `x0`, `a0` or `$a0`). This is supported on x86, x86_64,
+///   AArch64, RISC-V and LoongArch ELF targets using the local-exec model,
+///   which is only valid in executables, and on x86 and x86_64 Windows. The
+///   `att_syntax` option can't be used with these operands.
+/// - `sym(tls_ie)`: Like `sym(tls)`, but uses the initial-exec model on ELF
+///   targets, which loads the offset of the variable from the GOT. This is
+///   also valid in shared libraries which are loaded at program startup. On
+///   32-bit x86, `ebx` must hold the address of the GOT. On AArch64, `x17`
+///   (or `x16` if the address is computed into `x17`) is clobbered. On
+///   Windows this is the same as `sym(tls)`.
+/// - `sym(got)`: On 32-bit x86 ELF targets, references the GOT entry of the
+///   symbol as `symbol@GOT`, relative to the address of the GOT.
+/// - `sym(gotoff)`: On 32-bit x86 ELF targets, references the symbol as
+///   `symbol@GOTOFF`, its offset from the address of the GOT. This can only
+///   be used for symbols defined in the same module.
+/// - `sym(pm)`: On AVR, where code addresses are word addresses, references
+///   the program memory address of a function as `pm(symbol)`. For example
+///   this can be used for jump tables with `.word {}`. Use `pm_lo8({})` and
+///   `pm_hi8({})` with a plain `sym` operand to load the address into
+///   registers.
+/// - `sym(gs)`: On AVR, references the function as `gs(symbol)`, which makes
+///   the linker generate a stub for functions which are out of reach of
+///   `icall`/`ijmp` on devices with more than 128KiB of flash.
+///
+/// On MSP430, code and data share the same address space so `sym(pm)` and
+/// `sym(gs)` reference the symbol directly. This allows the same code to be
+/// used on both targets.
+///
+/// 32-bit x86 has no PC-relative addressing, so position-independent code must
+/// first compute the address of the GOT. When `sym(got)`, `sym(gotoff)` or
+/// `sym(tls_ie)` is used, the standard `__x86.get_pc_thunk.bx` helper is
+/// emitted in a COMDAT section so that it can be called from the function:
+///
+/// ```rust,ignore
+/// asm!(
+///     "push ebx",
+///     "call __x86.get_pc_thunk.bx",
+///     "add ebx, offset _GLOBAL_OFFSET_TABLE_",
+///     "mov eax, [ebx + {}]",
+///     "pop ebx",
+///     "ret",
+///     sym(gotoff) VALUE,
+/// );
+/// ```
+///
+/// ## Symbol names
+///
+/// Functions without `#[no_mangle]` or `#[export_name]` are given a symbol
+/// name using the legacy Rust mangling scheme, which demanglers display as
+/// `crate_name::function_name`. Proc macros don't know the module path of the
+/// item they are applied to, so it isn't included in the symbol: identically
+/// named functions in different modules all demangle to the same
+/// `crate_name::function_name`, and are only distinguished by the hash
+/// suffix of the symbol.
+///
+/// ## Native naked functions
+///
+/// When compiled with Rust 1.88 or later, which natively supports naked
+/// functions, the attribute instead expands to a `#[unsafe(naked)]` function
+/// whose body is a `naked_asm!` with the same operands. This provides proper
+/// debug info and diagnostics from rustc. In this case functions without
+/// `#[no_mangle]` or `#[export_name]` use the normal Rust symbol mangling.
+///
 /// ## Accessing function arguments.
 ///
 /// The function signature is indicative only: it is merely there to allow
@@ -79,17 +170,248 @@ mod naked;
 /// The function must have one of the following whitelisted ABIs:
 /// - `"C"`
 /// - `"C-unwind"`
+/// - `"system"`
+/// - `"system-unwind"`
+/// - `"sysv64"`
+/// - `"sysv64-unwind"`
+/// - `"win64"`
+/// - `"win64-unwind"`
+/// - `"efiapi"`
+/// - `"stdcall"`
+/// - `"stdcall-unwind"`
+/// - `"fastcall"`
+/// - `"fastcall-unwind"`
+/// - `"thiscall"`
+/// - `"thiscall-unwind"`
+///
+/// The `"vectorcall"` and `"vectorcall-unwind"` ABIs are supported with the
+/// `nightly` feature, since they are still unstable in Rust.
+///
+/// With the `nightly` feature enabled, the following interrupt ABIs are also
+/// supported:
+/// - `"x86-interrupt"`
+/// - `"riscv-interrupt-m"`
+/// - `"riscv-interrupt-s"`
+/// - `"msp430-interrupt"`
+/// - `"avr-interrupt"`
+/// - `"avr-non-blocking-interrupt"`
+///
+/// The `"cmse-nonsecure-entry"` ABI, which was called
+/// `"C-cmse-nonsecure-entry"` in older nightly versions of Rust, is also
+/// supported with the `nightly` feature. For these functions an additional
+/// `__acle_se_` symbol is emitted so that the linker can generate a secure
+/// gateway veneer.
+///
+/// Variadic functions such as `extern "C" fn f(fmt: *const u8, ...)` are
+/// supported with the `"C"`, `"C-unwind"`, `"sysv64"`, `"win64"` and
+/// `"efiapi"` ABIs. The variadic arguments are passed as specified by the
+/// calling convention of the target.
+///
+/// On WebAssembly targets the function type is declared with `.functype`,
+/// which is derived from the function signature. Only integer, floating-point,
+/// `bool`, `char`, pointer, reference and function pointer types are
+/// supported in the signature of these functions. Arguments are accessed as
+/// locals (`local.get 0`), and `end_function` is emitted automatically so it
+/// must not be included in the body.
+///
+/// Interrupt handlers can't be called from Rust, but their address can be
+/// taken to install them in a vector table. On AVR and MSP430, where the
+/// vector table is fixed at link time, the `avr::vector` and `msp430::vector`
+/// options place the handler in the vector table instead. Only
+/// `x86-interrupt` handlers may take arguments (the interrupt stack frame and
+/// optional error code), and interrupt handlers can't return a value.
+///
+/// The symbols of stdcall, fastcall and vectorcall functions are decorated
+/// with the size of their arguments where required by the target, matching
+/// the names used by rustc for the foreign declaration. The `raw` option can't
+/// be used with these ABIs when the function is lowered to `global_asm!`.
 ///
 /// Only the following attributes are supported on naked functions:
 /// - `#[export_name]`
 /// - `#[no_mangle]`
 /// - `#[link_section]`
 /// - `#[cfg]`
+/// - `#[instruction_set]`
 /// - `#[doc]` or `///` doc comments
+///
+/// In addition to the `arm::a32` and `arm::t32` instruction sets supported by
+/// rustc, `#[instruction_set(mips::mips16)]` and
+/// `#[instruction_set(mips::micromips)]` can be used on MIPS targets. Note
+/// that LLVM's assembler only has limited support for MIPS16 instructions.
+///
+/// The edition 2024 forms `#[unsafe(export_name)]`, `#[unsafe(no_mangle)]` and
+/// `#[unsafe(link_section)]` are also accepted.
+///
+/// ## Unwinding
+///
+/// Panics can only unwind through a naked function, and debuggers can only
+/// produce backtraces past it, if unwind info is emitted for it. This is
+/// controlled by the following options:
+/// - `unwind_tables`: Emits unwind info for the function. On Windows,
+///   functions using this option are always lowered to `global_asm!`.
+/// - `no_unwind_tables`: Doesn't emit any unwind info. This is useful for
+///   functions which provide their own, for example with hand-written
+///   `.cfi_startproc`/`.cfi_endproc` directives.
+///
+/// If neither option is specified then functions with an `-unwind` ABI, such
+/// as `extern "C-unwind"`, get call frame information on ELF and Mach-O
+/// targets, and other functions get no unwind info.
+///
+/// On ELF and Mach-O targets the function is bracketed with
+/// `.cfi_startproc`/`.cfi_endproc`, which initially describes the state at
+/// the function entry. Any changes to the stack pointer or to callee-saved
+/// registers must be described with CFI directives in the function body:
+///
+/// ```rust,ignore
+/// #[naked_function::naked]
+/// pub unsafe extern "C-unwind" fn trampoline(f: unsafe extern "C-unwind" fn()) {
+///     asm!(
+///         "push rbp",
+///         ".cfi_adjust_cfa_offset 8",
+///         ".cfi_offset rbp, -16",
+///         "call rdi",
+///         "pop rbp",
+///         ".cfi_adjust_cfa_offset -8",
+///         ".cfi_restore rbp",
+///         "ret",
+///     );
+/// }
+/// ```
+///
+/// On x86_64 and AArch64 Windows the function is bracketed with
+/// `.seh_proc`/`.seh_endproc`, which registers it in `.pdata`. The prologue
+/// must be described in the function body by placing SEH directives after the
+/// instructions they apply to, and ending it with `.seh_endprologue`:
+///
+/// ```rust,ignore
+/// #[naked_function::naked(unwind_tables)]
+/// pub unsafe extern "C-unwind" fn trampoline(f: unsafe extern "C-unwind" fn()) {
+///     asm!(
+///         "push rbp",
+///         ".seh_pushreg rbp",
+///         "sub rsp, 32",
+///         ".seh_stackalloc 32",
+///         ".seh_endprologue",
+///         "call rcx",
+///         "add rsp, 32",
+///         "pop rbp",
+///         "ret",
+///     );
+/// }
+/// ```
+///
+/// Leaf functions which don't modify the stack pointer don't need to declare
+/// a prologue.
+///
+/// The `entry_point` option is intended for functions such as `_start` or
+/// thread entry trampolines, which have no caller. It emits call frame
+/// information marking the return address as undefined
+/// (`.cfi_undefined rip`, `lr` or `ra` depending on the target), so that
+/// debuggers and unwinders stop at this function instead of walking into
+/// garbage. This is supported on ELF and Mach-O targets.
+///
+/// 32-bit ARM ELF targets use EHABI unwind tables instead, which also mark
+/// functions that can't be unwound through. Every naked function is bracketed
+/// with `.fnstart`/`.fnend`, and functions which don't emit unwind info
+/// according to the rules above get `.cantunwind`. Since EHABI doesn't track
+/// the position in the prologue, its effect on the stack is described with
+/// the following options, which must be listed in the order of the prologue:
+/// - `arm::save(regs...)`: Emits `.save {regs}` for a `push` of core
+///   registers.
+/// - `arm::vsave(regs...)`: Emits `.vsave {regs}` for a `vpush` of VFP
+///   registers.
+/// - `arm::pad(n)`: Emits `.pad #n` for a stack allocation of `n` bytes.
+///
+/// ```rust,ignore
+/// #[naked_function::naked(arm::save(r4, lr), arm::pad(8))]
+/// pub unsafe extern "C-unwind" fn trampoline(f: unsafe extern "C-unwind" fn()) {
+///     asm!(
+///         "push {{r4, lr}}",
+///         "sub sp, sp, #8",
+///         "blx r0",
+///         "add sp, sp, #8",
+///         "pop {{r4, pc}}",
+///     );
+/// }
+/// ```
+///
+/// ## Debug info
+///
+/// By default debuggers can't map the instructions of a naked function back
+/// to its source. The `debug_lines` option emits DWARF line information
+/// which maps each instruction to the template string in the `asm!` macro it
+/// was written in, so that debuggers can step through the function and show
+/// its source lines in backtraces. Only template strings written directly in
+/// the function body get line information; those produced by macros such as
+/// `concat!` are attributed to the preceding line.
+///
+/// Since rustc doesn't expose the `.file`/`.loc` numbering it uses for its
+/// own line table, the line information is emitted as a separate compilation
+/// unit containing a subprogram for the function, in the `.debug_info`,
+/// `.debug_abbrev` and `.debug_line` sections. Like rustc's own debug info,
+/// it is removed when debug info is stripped. The file name is the one rustc
+/// reports for the template strings, so it is affected by
+/// `--remap-path-prefix`. No compilation directory is recorded, so debuggers
+/// resolve relative paths against their own search paths.
+///
+/// This option is only supported on ELF targets and requires Rust 1.88 or
+/// later, which is needed for the source locations of the template strings.
+///
+/// ## Target-specific options
+///
+/// Options which only apply to some targets can be passed as arguments to
+/// the attribute, for example `#[naked(riscv::norelax)]`. Using an option on
+/// a target which doesn't support it is an error.
+///
+/// On RISC-V the following options are supported, which are scoped to the
+/// function with `.option push`/`.option pop`:
+/// - `riscv::norelax`/`riscv::relax`: Disables or enables linker relaxation.
+///   Relaxation must be disabled in `_start` code which initializes `gp`.
+/// - `riscv::norvc`/`riscv::rvc`: Disables or enables the use of compressed
+///   instructions.
+///
+/// Inline assembly is still unstable on some targets such as MIPS, in which
+/// case the crate using the attribute must enable
+/// `#![feature(asm_experimental_arch)]`.
+///
+/// On MIPS the following options are supported, which are scoped to the
+/// function with `.set push`/`.set pop`:
+/// - `mips::noreorder`: The assembler doesn't fill branch delay slots, which
+///   must be filled explicitly.
+/// - `mips::nomacro`: Disallows assembler macros which expand to multiple
+///   instructions.
+/// - `mips::noat`: Allows `$at` to be used without warnings.
+///
+/// On ARM the `arm::save`, `arm::vsave` and `arm::pad` options describe the
+/// prologue for unwinding, see above.
+///
+/// On AVR the following option is supported:
+/// - `avr::vector(n)`: Also defines the function as `__vector_n`, which is
+///   the symbol that the vector table of the avr-libc startup code jumps to
+///   for interrupt `n`.
+///
+/// On MSP430 the following option is supported:
+/// - `msp430::vector(n)`: Places the address of the function in the
+///   `__interrupt_vector_n` section, which the linker scripts for MSP430
+///   devices map to the corresponding entry of the vector table.
+///
+/// Functions using these options are always lowered to `global_asm!`, so that
+/// they are emitted even if they aren't referenced from Rust.
+///
+/// On PowerPC64 the following option is supported:
+/// - `powerpc64::toc`: On ELFv2 targets, emits a global entry point which sets
+///   up the TOC pointer in `r2` from `r12`, followed by a `.localentry`
+///   annotation so that callers sharing the same TOC skip it. On ELFv1
+///   targets the TOC pointer is loaded by the caller from the function
+///   descriptor, so this has no effect. Functions using this option are
+///   always lowered to `global_asm!`.
+///
+/// On PowerPC64 ELFv1 targets the function symbol refers to a function
+/// descriptor in `.opd`, which is emitted automatically.
 #[proc_macro_attribute]
 pub fn naked(attr: TokenStream, item: TokenStream) -> TokenStream {
-    parse_macro_input!(attr as Nothing);
-    match naked::naked_attribute(&parse_macro_input!(item)) {
+    let args = parse_macro_input!(attr as args::NakedArgs);
+    match naked::naked_attribute(&args, &parse_macro_input!(item)) {
         Ok(items) => {
             let mut tokens = TokenStream2::new();
             for item in &items {
@@ -0,0 +1,134 @@
+use std::{
+    env,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use proc_macro2::Ident;
+use syn::ext::IdentExt;
+
+/// Number of naked functions which have been expanded so far by this instance
+/// of the proc macro.
+///
+/// This distinguishes identically named functions within a single compilation
+/// of a crate, such as functions in different modules of the same file. It
+/// isn't an identity which is stable across compilations.
+static FUNCTION_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+/// 64-bit FNV-1a hash.
+///
+/// Unlike `DefaultHasher`, this is guaranteed to produce the same results
+/// across compiler versions, which keeps symbol names reproducible.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    /// Hashes a field followed by a terminator so that adjacent fields can't
+    /// be confused with each other.
+    fn write_field(&mut self, bytes: &[u8]) {
+        for &byte in bytes.iter().chain(&[0xff]) {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+/// Number of unique label prefixes which have been generated so far in the
+/// crate being compiled.
+static LABEL_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the name of the crate currently being compiled.
+///
+/// This is provided by Cargo through the environment of the rustc process the
+/// proc macro is running in.
+fn crate_name() -> String {
+    env::var("CARGO_CRATE_NAME").unwrap_or_else(|_| "unknown".into())
+}
+
+/// Appends an identifier to a legacy-mangled symbol name.
+///
+/// Each path component is encoded as its length followed by its contents.
+/// Characters which aren't valid in a symbol name are escaped as `$u{hex}$`,
+/// which is understood by Rust demanglers.
+fn push_ident(out: &mut String, ident: &str) {
+    let mut escaped = String::new();
+    for c in ident.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            escaped.push(c);
+        } else {
+            escaped.push_str(&format!("$u{:x}$", c as u32));
+        }
+    }
+    out.push_str(&escaped.len().to_string());
+    out.push_str(&escaped);
+}
+
+/// Computes the symbol name for a naked function which doesn't have a
+/// `#[no_mangle]` or `#[export_name]` attribute.
+///
+/// The symbol follows the legacy Rust mangling scheme (`_ZN...E`) so that
+/// demanglers such as `rustfilt`, `addr2line` and `perf` display it as
+/// `crate_name::function_name`. The module path of the function isn't
+/// included.
+///
+/// Proc macros have no access to the module path of the item they are
+/// applied to, so functions are instead disambiguated by the hash component
+/// of the symbol. This is computed from the identity of the crate and of the
+/// Cargo target being compiled, the source file of the function and its
+/// position among the naked functions expanded in this compilation. This
+/// ensures that identically named functions in different modules, in the
+/// library and binaries of a package, or in different crates, don't collide at
+/// link time.
+pub fn default_symbol(ident: &Ident) -> String {
+    let index = FUNCTION_INDEX.fetch_add(1, Ordering::Relaxed);
+
+    let mut symbol = String::from("_ZN");
+    push_ident(&mut symbol, &crate_name());
+    push_ident(&mut symbol, &ident.unraw().to_string());
+    push_ident(&mut symbol, &format!("h{:016x}", item_hash(ident, index)));
+    symbol.push('E');
+    symbol
+}
+
+/// Hashes the crate and target identity together with the name, source file
+/// and index of an item.
+fn item_hash(ident: &Ident, index: usize) -> u64 {
+    let mut hash = Fnv1a::new();
+    hash.write_field(crate_name().as_bytes());
+    // The library and binaries of a package all have the same crate name and
+    // version, but only binaries have `CARGO_BIN_NAME` set.
+    for var in ["CARGO_PKG_VERSION", "CARGO_MANIFEST_DIR", "CARGO_BIN_NAME"] {
+        hash.write_field(env::var(var).unwrap_or_default().as_bytes());
+    }
+    hash.write_field(source_file(ident).as_bytes());
+    hash.write_field(ident.unraw().to_string().as_bytes());
+    hash.write_field(&(index as u64).to_le_bytes());
+    hash.0
+}
+
+/// Returns the path of the source file containing an identifier.
+#[cfg(proc_macro_span)]
+#[allow(clippy::incompatible_msrv)] // Only built on Rust 1.88 or later.
+fn source_file(ident: &Ident) -> String {
+    ident.span().unwrap().file()
+}
+
+/// Spans only expose their source file since Rust 1.88, before which the other
+/// components of the hash have to suffice.
+#[cfg(not(proc_macro_span))]
+fn source_file(_ident: &Ident) -> String {
+    String::new()
+}
+
+/// Returns a prefix for assembler-local labels which is unique to one naked
+/// function.
+///
+/// Local labels only need to be unique within an object file, but object
+/// files may contain code from multiple crates when using LTO, so the prefix
+/// is derived from the crate identity like the default symbol names.
+pub fn unique_label_prefix(ident: &Ident) -> String {
+    let index = LABEL_INDEX.fetch_add(1, Ordering::Relaxed);
+    format!(".L__naked_function_{:016x}", item_hash(ident, index))
+}
@@ -1,12 +1,88 @@
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{quote, ToTokens};
 use syn::{
-    punctuated::Punctuated, Abi, AttrStyle, Attribute, Expr, ExprLit, ExprMacro, ForeignItem,
-    ForeignItemFn, Item, ItemFn, ItemForeignMod, ItemMacro, Lit, LitStr, Macro, MacroDelimiter,
-    Meta, MetaNameValue, Result, Signature, Token,
+    ext::IdentExt,
+    parse_quote,
+    punctuated::Punctuated,
+    visit_mut::{self, VisitMut},
+    Abi, AttrStyle, Attribute, BoundLifetimes, Expr, ExprLit, ExprMacro, FnArg, ForeignItem,
+    ForeignItemFn, GenericParam, Item, ItemFn, ItemForeignMod, ItemMacro, Lifetime, Lit, LitStr,
+    Macro, MacroDelimiter, Meta, MetaNameValue, Path, Result, ReturnType, Signature, Token,
+    TraitBound, Type, TypeBareFn, Variadic,
 };
 
-use crate::asm::{extract_asm, AsmOperand};
+use crate::{
+    args::NakedArgs,
+    asm::{apply_sym_modifiers, extract_asm, AsmOperand},
+    mangle::{default_symbol, unique_label_prefix},
+};
+
+/// ABIs which naked functions may be declared with.
+const ABI_WHITELIST: &[&str] = &[
+    "C",
+    "C-unwind",
+    "system",
+    "system-unwind",
+    "sysv64",
+    "sysv64-unwind",
+    "win64",
+    "win64-unwind",
+    "efiapi",
+    "stdcall",
+    "stdcall-unwind",
+    "fastcall",
+    "fastcall-unwind",
+    "thiscall",
+    "thiscall-unwind",
+];
+
+/// ABIs which support variadic functions.
+const VARIADIC_ABIS: &[&str] = &["C", "C-unwind", "sysv64", "win64", "efiapi"];
+
+/// ABIs which are unstable and therefore require the `nightly` feature.
+const NIGHTLY_ABIS: &[&str] = &[
+    "vectorcall",
+    "vectorcall-unwind",
+    "x86-interrupt",
+    "riscv-interrupt-m",
+    "riscv-interrupt-s",
+    "msp430-interrupt",
+    "avr-interrupt",
+    "avr-non-blocking-interrupt",
+    "C-cmse-nonsecure-entry",
+    "cmse-nonsecure-entry",
+];
+
+/// Checks the additional restrictions that interrupt ABIs place on the function
+/// signature.
+fn validate_interrupt_sig(abi: &str, sig: &Signature) -> Result<()> {
+    // x86 interrupt handlers receive a pointer to the interrupt stack frame,
+    // optionally followed by an error code. Other interrupt ABIs don't take any
+    // arguments.
+    if abi == "x86-interrupt" {
+        if sig.inputs.is_empty() || sig.inputs.len() > 2 {
+            bail!(
+                sig,
+                "`extern \"x86-interrupt\"` functions must take either one or two arguments"
+            );
+        }
+    } else if !sig.inputs.is_empty() {
+        bail!(
+            &sig.inputs,
+            "`extern \"{}\"` functions cannot take arguments",
+            abi
+        );
+    }
+    match &sig.output {
+        ReturnType::Default => {}
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::Never(_) => {}
+            Type::Tuple(tuple) if tuple.elems.is_empty() => {}
+            _ => bail!(ty, "`extern \"{}\"` functions cannot return a value", abi),
+        },
+    }
+    Ok(())
+}
 
 /// Sanity checks the function signature.
 fn validate_sig(sig: &Signature) -> Result<()> {
@@ -23,12 +99,42 @@ fn validate_sig(sig: &Signature) -> Result<()> {
         Some(Abi {
             extern_token: _,
             name: Some(name),
-        }) if matches!(&*name.value(), "C" | "C-unwind") => {}
+        }) if ABI_WHITELIST.contains(&&*name.value()) => {}
+        Some(Abi {
+            extern_token: _,
+            name: Some(name),
+        }) if NIGHTLY_ABIS.contains(&&*name.value()) => {
+            if !cfg!(feature = "nightly") {
+                bail!(
+                    name,
+                    "`extern \"{}\"` requires the `nightly` feature of the naked-function crate",
+                    name.value()
+                );
+            }
+            if name.value().contains("interrupt") {
+                validate_interrupt_sig(&name.value(), sig)?;
+            }
+        }
         _ => bail!(
             &sig.abi,
-            "#[naked] functions must be `extern \"C\"` or `extern \"C-unwind\"`"
+            "#[naked] functions must use one of the following ABIs: {}",
+            ABI_WHITELIST
+                .iter()
+                .map(|abi| format!("`extern \"{}\"`", abi))
+                .collect::<Vec<_>>()
+                .join(", ")
         ),
     }
+    if let Some(variadic) = &sig.variadic {
+        let abi = sig.abi.as_ref().unwrap().name.as_ref().unwrap().value();
+        if !VARIADIC_ABIS.contains(&&*abi) {
+            bail!(
+                variadic,
+                "variadic functions are not supported with `extern \"{}\"`",
+                abi
+            );
+        }
+    }
     if !sig.generics.params.is_empty() {
         bail!(
             &sig.generics,
@@ -38,12 +144,32 @@ fn validate_sig(sig: &Signature) -> Result<()> {
     Ok(())
 }
 
+#[derive(Clone)]
 struct ParsedAttrs {
     foreign_attrs: Vec<Attribute>,
+    native_attrs: Vec<Attribute>,
     cfg: Vec<Attribute>,
     symbol: Expr,
     link_section: Expr,
-    instruction_set: Option<TokenStream>,
+    instruction_set: Option<Path>,
+}
+
+/// Returns whether the instruction set is one of the ARM instruction sets
+/// supported by rustc's `#[instruction_set]` attribute.
+fn is_arm_isa(isa: &Path) -> bool {
+    isa.segments
+        .first()
+        .map_or(false, |segment| segment.ident == "arm")
+}
+
+/// Builds an outer attribute from the given meta item wrapped in `unsafe(...)`.
+fn unsafe_attr(meta: &Meta) -> Attribute {
+    Attribute {
+        pound_token: Default::default(),
+        style: AttrStyle::Outer,
+        bracket_token: Default::default(),
+        meta: syn::parse2(quote!(unsafe(#meta))).unwrap(),
+    }
 }
 
 /// Parses the attributes on the function and checks them against a whitelist
@@ -53,14 +179,15 @@ struct ParsedAttrs {
 /// are computed here based on the function attributes.
 fn parse_attrs(ident: &Ident, attrs: &[Attribute]) -> Result<ParsedAttrs> {
     let mut foreign_attrs = vec![];
+    let mut native_attrs = vec![];
     let mut cfg = vec![];
     let mut no_mangle = false;
     let mut export_name = None;
     let mut link_section = None;
     let mut instruction_set = None;
 
-    // Attributes to forward to the foreign function declaration that we will
-    // generate.
+    // Attributes to forward to the foreign function declaration or native
+    // naked function that we will generate.
     let attr_whitelist = [
         "doc",
         "allow",
@@ -80,10 +207,19 @@ fn parse_attrs(ident: &Ident, attrs: &[Attribute]) -> Result<ParsedAttrs> {
         for whitelist in attr_whitelist {
             if attr.path().is_ident(whitelist) {
                 foreign_attrs.push(attr.clone());
+                native_attrs.push(attr.clone());
                 continue 'outer;
             }
         }
 
+        // Edition 2024 requires some attributes to be wrapped in `unsafe(...)`.
+        let unsafe_meta = if attr.path().is_ident("unsafe") {
+            Some(attr.parse_args::<Meta>()?)
+        } else {
+            None
+        };
+        let meta = unsafe_meta.as_ref().unwrap_or(&attr.meta);
+
         if attr
             .path()
             .segments
@@ -91,13 +227,14 @@ fn parse_attrs(ident: &Ident, attrs: &[Attribute]) -> Result<ParsedAttrs> {
             .map_or(false, |segment| segment.ident == "rustfmt")
         {
             // Ignore rustfmt attributes
-        } else if attr.path().is_ident("no_mangle") {
-            attr.meta.require_path_only()?;
+        } else if meta.path().is_ident("no_mangle") {
+            meta.require_path_only()?;
             no_mangle = true;
-        } else if attr.path().is_ident("export_name") {
+            native_attrs.push(unsafe_attr(meta));
+        } else if meta.path().is_ident("export_name") {
             // Pass the export_name attribute through as a #[link_section] on
             // the foreign import declaration.
-            let name_value = attr.meta.require_name_value()?;
+            let name_value = meta.require_name_value()?;
             export_name = Some(name_value.value.clone());
             let mut link_name = attr.clone();
             link_name.meta = Meta::NameValue(MetaNameValue {
@@ -106,13 +243,20 @@ fn parse_attrs(ident: &Ident, attrs: &[Attribute]) -> Result<ParsedAttrs> {
                 value: name_value.value.clone(),
             });
             foreign_attrs.push(link_name);
-        } else if attr.path().is_ident("link_section") {
-            let name_value = attr.meta.require_name_value()?;
+            native_attrs.push(unsafe_attr(meta));
+        } else if meta.path().is_ident("link_section") {
+            let name_value = meta.require_name_value()?;
             link_section = Some(name_value.value.clone());
+            native_attrs.push(unsafe_attr(meta));
         } else if attr.path().is_ident("cfg") {
             cfg.push(attr.clone())
         } else if attr.path().is_ident("instruction_set") {
-            instruction_set = Some(attr.meta.require_list()?.tokens.clone());
+            let isa: Path = attr.parse_args()?;
+            // rustc only accepts ARM instruction sets in #[instruction_set].
+            if is_arm_isa(&isa) {
+                native_attrs.push(attr.clone());
+            }
+            instruction_set = Some(isa);
         } else {
             bail!(
                 attr,
@@ -126,9 +270,9 @@ fn parse_attrs(ident: &Ident, attrs: &[Attribute]) -> Result<ParsedAttrs> {
         export_name.clone()
     } else {
         let raw_symbol = if no_mangle {
-            ident.to_string()
+            ident.unraw().to_string()
         } else {
-            format!("rust_naked_function_{}", ident.to_string())
+            default_symbol(ident)
         };
 
         Expr::Lit(ExprLit {
@@ -171,6 +315,7 @@ fn parse_attrs(ident: &Ident, attrs: &[Attribute]) -> Result<ParsedAttrs> {
 
     Ok(ParsedAttrs {
         foreign_attrs,
+        native_attrs,
         cfg,
         symbol,
         link_section,
@@ -181,9 +326,14 @@ fn parse_attrs(ident: &Ident, attrs: &[Attribute]) -> Result<ParsedAttrs> {
 fn emit_foreign_mod(func: &ItemFn, attrs: &ParsedAttrs) -> ItemForeignMod {
     // Remove the ABI and unsafe from the function signature and move it to the
     // `extern` block.
+    // Patterns are not allowed on the variadic argument of a foreign function.
     let sig = Signature {
         abi: None,
         unsafety: None,
+        variadic: func.sig.variadic.clone().map(|variadic| Variadic {
+            pat: None,
+            ..variadic
+        }),
         ..func.sig.clone()
     };
     let foreign_fn = ForeignItem::Fn(ForeignItemFn {
@@ -198,36 +348,361 @@ fn emit_foreign_mod(func: &ItemFn, attrs: &ParsedAttrs) -> ItemForeignMod {
     });
     ItemForeignMod {
         attrs: vec![],
-        unsafety: None,
+        // Edition 2024 requires extern blocks to be marked as unsafe.
+        unsafety: if cfg!(unsafe_extern_blocks) {
+            Some(Default::default())
+        } else {
+            None
+        },
         abi: func.sig.abi.clone().unwrap(),
         brace_token: Default::default(),
         items: vec![foreign_fn],
     }
 }
 
-fn emit_global_asm(attrs: &ParsedAttrs, mut asm: Punctuated<AsmOperand, Token![,]>) -> ItemMacro {
-    // Inject a prefix to the assembly code containing the necessary assembler
-    // directives to start a function.
-    let symbol = &attrs.symbol;
-    let link_section = &attrs.link_section;
-    let instruction_set = &attrs.instruction_set;
-    let prefix = syn::parse2(quote! {
-        ::naked_function::__asm_function_begin!(#symbol, #link_section, (#instruction_set))
-    })
-    .unwrap();
-    asm.insert(0, AsmOperand::Template(prefix));
+/// Returns the ABI of the function without any `-unwind` suffix.
+fn base_abi(sig: &Signature) -> LitStr {
+    let name = sig.abi.as_ref().unwrap().name.as_ref().unwrap();
+    let value = name.value();
+    LitStr::new(value.strip_suffix("-unwind").unwrap_or(&value), name.span())
+}
 
-    // Inject a suffix at the end of the assembly code containing assembler
-    // directives to end a function.
+/// Returns whether the function has an ABI which allows unwinding.
+fn is_unwind_abi(sig: &Signature) -> bool {
+    let name = sig.abi.as_ref().unwrap().name.as_ref().unwrap();
+    name.value().ends_with("-unwind")
+}
+
+/// Returns whether the symbol of a function with the given ABI may be
+/// decorated with the size of its arguments.
+fn has_arg_size_decoration(abi: &str) -> bool {
+    matches!(abi, "stdcall" | "fastcall" | "vectorcall")
+}
+
+/// Replaces all lifetimes in a type with `'_`.
+///
+/// This allows argument types to be named outside the function signature where
+/// its lifetime parameters are not in scope. Lifetimes declared by `for<...>`
+/// binders within the type are kept, since `'_` can't be declared there.
+#[derive(Default)]
+struct EraseLifetimes {
+    /// Lifetimes declared by the binders enclosing the visited node.
+    bound: Vec<Lifetime>,
+}
+
+impl EraseLifetimes {
+    /// Adds the lifetimes declared by a binder to the ones in scope, returning
+    /// the previous number of lifetimes in scope.
+    fn bind(&mut self, lifetimes: &Option<BoundLifetimes>) -> usize {
+        let len = self.bound.len();
+        if let Some(lifetimes) = lifetimes {
+            self.bound
+                .extend(lifetimes.lifetimes.iter().filter_map(|param| match param {
+                    GenericParam::Lifetime(param) => Some(param.lifetime.clone()),
+                    _ => None,
+                }));
+        }
+        len
+    }
+}
+
+impl VisitMut for EraseLifetimes {
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if !self.bound.contains(lifetime) {
+            *lifetime = Lifetime::new("'_", lifetime.span());
+        }
+    }
+
+    fn visit_type_bare_fn_mut(&mut self, ty: &mut TypeBareFn) {
+        let len = self.bind(&ty.lifetimes);
+        visit_mut::visit_type_bare_fn_mut(self, ty);
+        self.bound.truncate(len);
+    }
+
+    fn visit_trait_bound_mut(&mut self, bound: &mut TraitBound) {
+        let len = self.bind(&bound.lifetimes);
+        visit_mut::visit_trait_bound_mut(self, bound);
+        self.bound.truncate(len);
+    }
+}
+
+/// Builds a `const` operand with the total size of the function arguments, as
+/// used in the symbol decoration of the stdcall, fastcall and vectorcall
+/// ABIs.
+///
+/// This can't be computed by the proc macro since it depends on the layout of
+/// the argument types.
+fn arg_size_operand(sig: &Signature) -> AsmOperand {
+    let sizes = sig.inputs.iter().filter_map(|arg| match arg {
+        FnArg::Typed(pat_type) => {
+            let mut ty = (*pat_type.ty).clone();
+            EraseLifetimes::default().visit_type_mut(&mut ty);
+            Some(quote!(::naked_function::__arg_size::<#ty>()))
+        }
+        FnArg::Receiver(_) => None,
+    });
+    AsmOperand::Const {
+        name: Some((
+            Ident::new("__naked_function_arg_size", Span::call_site()),
+            Default::default(),
+        )),
+        token: Default::default(),
+        expr: syn::parse2(quote!(0 #(+ #sizes)*)).unwrap(),
+    }
+}
+
+/// Returns the WebAssembly value type that an argument or return value of the
+/// given type is passed as.
+///
+/// Pointer-sized types are returned as `ptr` since their size depends on the
+/// target, and types which aren't passed as a single value are returned as
+/// `unknown`. These are resolved by the `__asm_functype!` helper macro.
+fn wasm_type(ty: &Type) -> &'static str {
+    match ty {
+        Type::Ptr(_) | Type::Reference(_) | Type::BareFn(_) => "ptr",
+        Type::Paren(paren) => wasm_type(&paren.elem),
+        Type::Group(group) => wasm_type(&group.elem),
+        Type::Path(path) if path.qself.is_none() => {
+            match &*path.path.segments.last().unwrap().ident.to_string() {
+                "i8" | "u8" | "i16" | "u16" | "i32" | "u32" | "bool" | "char" | "c_char"
+                | "c_schar" | "c_uchar" | "c_short" | "c_ushort" | "c_int" | "c_uint" => "i32",
+                "i64" | "u64" | "c_longlong" | "c_ulonglong" => "i64",
+                "f32" | "c_float" => "f32",
+                "f64" | "c_double" => "f64",
+                "isize" | "usize" | "c_long" | "c_ulong" => "ptr",
+                _ => "unknown",
+            }
+        }
+        _ => "unknown",
+    }
+}
+
+/// Builds the template declaring the WebAssembly function type of the
+/// function, which is required by the assembler on wasm targets.
+fn functype_template(sig: &Signature, symbol: &Expr) -> AsmOperand {
+    let params = sig.inputs.iter().map(|arg| match arg {
+        FnArg::Typed(pat_type) => Ident::new(wasm_type(&pat_type.ty), Span::call_site()),
+        FnArg::Receiver(_) => Ident::new("unknown", Span::call_site()),
+    });
+    let result = match &sig.output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::Never(_) => None,
+            Type::Tuple(tuple) if tuple.elems.is_empty() => None,
+            ty => Some(Ident::new(wasm_type(ty), Span::call_site())),
+        },
+    };
+    AsmOperand::Template(
+        syn::parse2(quote! {
+            ::naked_function::__asm_functype!(#symbol, (#(#params),*), (#result))
+        })
+        .unwrap(),
+    )
+}
+
+/// Inserts templates at the start and after the end of the assembly code.
+fn wrap_asm(asm: &mut Punctuated<AsmOperand, Token![,]>, prefix: TokenStream, suffix: TokenStream) {
+    asm.insert(0, AsmOperand::Template(syn::parse2(prefix).unwrap()));
     let last_template = asm
         .iter()
         .rposition(|op| matches!(op, AsmOperand::Template(_)))
         .unwrap();
-    let suffix = syn::parse2(quote! {
-        ::naked_function::__asm_function_end!(#symbol)
-    })
-    .unwrap();
-    asm.insert(last_template + 1, AsmOperand::Template(suffix));
+    asm.insert(
+        last_template + 1,
+        AsmOperand::Template(syn::parse2(suffix).unwrap()),
+    );
+}
+
+/// Injects the directives describing how to unwind through the function
+/// around its body.
+fn wrap_unwind_info(
+    args: &NakedArgs,
+    sig: &Signature,
+    attrs: &ParsedAttrs,
+    asm: &mut Punctuated<AsmOperand, Token![,]>,
+) {
+    let unwind = args.unwind_tables.unwrap_or_else(|| is_unwind_abi(sig));
+
+    // Entry points need call frame information to mark the return address as
+    // undefined, even if the function can't be unwound through.
+    if args.entry_point {
+        let entry_point = quote!(::naked_function::__asm_entry_point!());
+        asm.insert(0, AsmOperand::Template(syn::parse2(entry_point).unwrap()));
+    }
+    if args.unwind_tables == Some(true) {
+        let symbol = &attrs.symbol;
+        let abi = base_abi(sig);
+        wrap_asm(
+            asm,
+            quote!(::naked_function::__asm_unwind_begin!(#symbol, #abi)),
+            quote!(::naked_function::__asm_unwind_end!()),
+        );
+    } else if unwind || args.entry_point {
+        wrap_asm(
+            asm,
+            quote!(::naked_function::__asm_cfi_begin!()),
+            quote!(::naked_function::__asm_cfi_end!()),
+        );
+    }
+
+    // ARM EHABI uses its own unwind tables, which must also mark functions
+    // that can't be unwound through.
+    let mode = match args.unwind_tables {
+        Some(false) if args.target_options.is_empty() => return,
+        Some(false) => quote!(none),
+        _ if unwind => quote!(unwind),
+        _ => quote!(cantunwind),
+    };
+    let target_options = &args.target_options;
+    wrap_asm(
+        asm,
+        quote!(::naked_function::__asm_ehabi_begin!(#mode, (#(#target_options),*))),
+        quote!(::naked_function::__asm_ehabi_end!(#mode)),
+    );
+}
+
+/// Escapes a string so that it can be used in a quoted string in the assembly
+/// code.
+fn escape_asm_string(s: &str, raw: bool) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        match c {
+            '\\' | '"' => escaped.push('\\'),
+            '{' | '}' if !raw => escaped.push(c),
+            _ => {}
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Labels each template line of the function and emits DWARF line number
+/// information mapping the labels to the source lines of the templates.
+///
+/// This can't be done with `.file`/`.loc` directives because file numbers are
+/// shared by all the assembly code in an object file, and can only be
+/// declared once.
+fn add_debug_lines(ident: &Ident, asm: &mut Punctuated<AsmOperand, Token![,]>) -> Result<()> {
+    let raw = asm.iter().any(|op| match op {
+        AsmOperand::Options { options, .. } => options.iter().any(|option| option == "raw"),
+        _ => false,
+    });
+    let prefix = unique_label_prefix(ident);
+    let label = |name: &str| LitStr::new(&format!("{}:", name), Span::call_site());
+    if !cfg!(proc_macro_span) {
+        bail!(
+            ident,
+            "the `debug_lines` option requires Rust 1.88 or later"
+        );
+    }
+
+    // Only templates which are string literals in the same file as the first
+    // one have a known source location.
+    let mut file = None;
+    let mut rows = vec![];
+    let mut line = 1;
+    let mut labelled = Punctuated::new();
+    for op in std::mem::take(asm) {
+        if let AsmOperand::Template(Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        })) = &op
+        {
+            let (lit_file, lit_line, lit_column) = source_location(lit);
+            if *file.get_or_insert_with(|| lit_file.clone()) == lit_file {
+                let row = format!("{}_{}", prefix, rows.len());
+                let row_label = label(&row);
+                labelled.push(AsmOperand::Template(syn::parse_quote!(#row_label)));
+                rows.push((
+                    row,
+                    (lit_line as i64 - line).to_string(),
+                    lit_column.to_string(),
+                ));
+                line = lit_line as i64;
+            }
+        }
+        labelled.push(op);
+    }
+    *asm = labelled;
+    let file = match file {
+        Some(file) => file,
+        None => return Ok(()),
+    };
+
+    let name = escape_asm_string(&ident.unraw().to_string(), raw);
+    let file = escape_asm_string(&file, raw);
+    let row_labels = rows.iter().map(|row| &row.0);
+    let advances = rows.iter().map(|row| &row.1);
+    let columns = rows.iter().map(|row| &row.2);
+    let start = label(&format!("{}_start", prefix));
+    let end = label(&format!("{}_end", prefix));
+    wrap_asm(asm, quote!(#start), quote!(#end));
+    let last_template = asm
+        .iter()
+        .rposition(|op| matches!(op, AsmOperand::Template(_)))
+        .unwrap();
+    let debug_lines = quote! {
+        ::naked_function::__asm_debug_lines!(
+            #prefix, #name, #file, #((#row_labels, #advances, #columns)),*
+        )
+    };
+    asm.insert(
+        last_template + 1,
+        AsmOperand::Template(syn::parse2(debug_lines).unwrap()),
+    );
+    Ok(())
+}
+
+/// Returns the source file, line and column of a template string.
+#[cfg(proc_macro_span)]
+#[allow(clippy::incompatible_msrv)] // Only built on Rust 1.88 or later.
+fn source_location(lit: &LitStr) -> (String, usize, usize) {
+    let span = lit.span().unwrap();
+    (span.file(), span.line(), span.column())
+}
+
+/// Spans only expose their source location since Rust 1.88, which is checked
+/// before this is called.
+#[cfg(not(proc_macro_span))]
+fn source_location(_lit: &LitStr) -> (String, usize, usize) {
+    unreachable!()
+}
+
+fn emit_global_asm(
+    args: &NakedArgs,
+    sig: &Signature,
+    attrs: &ParsedAttrs,
+    mut asm: Punctuated<AsmOperand, Token![,]>,
+) -> ItemMacro {
+    // Pass the size of the arguments for ABIs which include it in the symbol
+    // name. Operands must come before any options.
+    let abi = base_abi(sig);
+    if has_arg_size_decoration(&abi.value()) {
+        let options = asm
+            .iter()
+            .position(|op| matches!(op, AsmOperand::Options { .. }))
+            .unwrap_or(asm.len());
+        asm.insert(options, arg_size_operand(sig));
+    }
+
+    // Inject a prefix and suffix to the assembly code containing the necessary
+    // assembler directives to start and end a function.
+    let symbol = &attrs.symbol;
+    let link_section = &attrs.link_section;
+    let instruction_set = &attrs.instruction_set;
+    let target_options = &args.target_options;
+    wrap_unwind_info(args, sig, attrs, &mut asm);
+    wrap_asm(
+        &mut asm,
+        quote! {
+            ::naked_function::__asm_function_begin!(
+                #symbol, #abi, #link_section, (#instruction_set), (#(#target_options),*)
+            )
+        },
+        quote! {
+            ::naked_function::__asm_function_end!(#symbol, #abi, (#(#target_options),*))
+        },
+    );
+    asm.insert(1, functype_template(sig, symbol));
 
     let global_asm = Macro {
         path: syn::parse2(quote!(::core::arch::global_asm)).unwrap(),
@@ -243,12 +718,169 @@ fn emit_global_asm(attrs: &ParsedAttrs, mut asm: Punctuated<AsmOperand, Token![,
     }
 }
 
+/// Returns whether the function must be lowered to `global_asm!` even when
+/// native naked functions are available.
+fn requires_global_asm(args: &NakedArgs, sig: &Signature, attrs: &ParsedAttrs) -> bool {
+    // rustc doesn't emit the secure entry symbol for native naked functions.
+    if matches!(
+        &*base_abi(sig).value(),
+        "C-cmse-nonsecure-entry" | "cmse-nonsecure-entry"
+    ) {
+        return true;
+    }
+
+    // rustc doesn't emit a global entry point which sets up the TOC pointer
+    // for native naked functions on PowerPC64.
+    if args.has_target_option("powerpc64", "toc") {
+        return true;
+    }
+
+    // Interrupt handlers placed in a vector table are usually not referenced
+    // from Rust, in which case rustc wouldn't emit a native naked function.
+    if args.has_target_option("avr", "vector") || args.has_target_option("msp430", "vector") {
+        return true;
+    }
+
+    // Instruction sets not supported by rustc, such as microMIPS, are only
+    // selected by our own directives.
+    if attrs
+        .instruction_set
+        .as_ref()
+        .map_or(false, |isa| !is_arm_isa(isa))
+    {
+        return true;
+    }
+
+    // Defining variadic functions requires the unstable `c_variadic` feature,
+    // but declaring them in an `extern` block doesn't.
+    if sig.variadic.is_some() {
+        return true;
+    }
+
+    false
+}
+
+/// Emits a native naked function, which is supported by rustc since 1.88.
+///
+/// This gives the function proper debug info and diagnostics, and lets rustc
+/// handle the platform-specific details of defining a function in assembly.
+fn emit_native_fn(
+    args: &NakedArgs,
+    func: &ItemFn,
+    attrs: &ParsedAttrs,
+    mut asm: Punctuated<AsmOperand, Token![,]>,
+) -> ItemFn {
+    // rustc emits the function label itself, so only the directives for
+    // unwind info and target-specific options need to be added around the
+    // body.
+    wrap_unwind_info(args, &func.sig, attrs, &mut asm);
+    if !args.target_options.is_empty() {
+        let target_options = &args.target_options;
+        wrap_asm(
+            &mut asm,
+            quote!(::naked_function::__asm_target_options_begin!(#(#target_options),*)),
+            quote!(::naked_function::__asm_target_options_end!(#(#target_options),*)),
+        );
+    }
+
+    let mut native_attrs = attrs.native_attrs.clone();
+    native_attrs.extend_from_slice(&attrs.cfg[..]);
+    native_attrs.push(unsafe_attr(&syn::parse2(quote!(naked)).unwrap()));
+    let block = syn::parse2(quote! {
+        {
+            ::core::arch::naked_asm!(#asm)
+        }
+    })
+    .unwrap();
+    ItemFn {
+        attrs: native_attrs,
+        vis: func.vis.clone(),
+        sig: func.sig.clone(),
+        block,
+    }
+}
+
 /// Entry point of the proc macro.
-pub fn naked_attribute(func: &ItemFn) -> Result<Vec<Item>> {
+pub fn naked_attribute(args: &NakedArgs, func: &ItemFn) -> Result<Vec<Item>> {
     validate_sig(&func.sig)?;
-    let attrs = parse_attrs(&func.sig.ident, &func.attrs)?;
-    let asm = extract_asm(func)?;
+    let mut attrs = parse_attrs(&func.sig.ident, &func.attrs)?;
+    let mut asm = extract_asm(func)?;
+    apply_sym_modifiers(&mut asm)?;
+    if args.debug_lines {
+        add_debug_lines(&func.sig.ident, &mut asm)?;
+    }
+
+    let mut items = vec![];
+    if cfg!(native_naked_functions) && !requires_global_asm(args, &func.sig, &attrs) {
+        if args.unwind_tables != Some(true) {
+            return Ok(vec![Item::Fn(emit_native_fn(args, func, &attrs, asm))]);
+        }
+
+        // SEH directives need the symbol name, which is only known to us when
+        // lowering to `global_asm!`. Other targets only need CFI directives,
+        // which work in native naked functions.
+        let mut native_attrs = attrs.clone();
+        native_attrs.cfg.push(parse_quote!(#[cfg(not(windows))]));
+        items.push(Item::Fn(emit_native_fn(
+            args,
+            func,
+            &native_attrs,
+            asm.clone(),
+        )));
+        attrs.cfg.push(parse_quote!(#[cfg(windows)]));
+    }
+
+    // When lowering to `global_asm!`, the symbol decoration is emitted with an
+    // operand, which doesn't work with raw templates. Native naked functions
+    // are decorated by rustc instead.
+    if has_arg_size_decoration(&base_abi(&func.sig).value()) {
+        for op in &asm {
+            if let AsmOperand::Options { options, .. } = op {
+                if let Some(raw) = options.iter().find(|option| *option == "raw") {
+                    bail!(
+                        raw,
+                        "the `raw` option cannot be used with the {} ABI",
+                        base_abi(&func.sig).value()
+                    );
+                }
+            }
+        }
+    }
     let foreign_mod = emit_foreign_mod(func, &attrs);
-    let global_asm = emit_global_asm(&attrs, asm);
-    Ok(vec![Item::ForeignMod(foreign_mod), Item::Macro(global_asm)])
+    let global_asm = emit_global_asm(args, &func.sig, &attrs, asm);
+    items.push(Item::ForeignMod(foreign_mod));
+    items.push(Item::Macro(global_asm));
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Erases the lifetimes in a type and returns the result.
+    fn erase_lifetimes(mut ty: Type) -> String {
+        EraseLifetimes::default().visit_type_mut(&mut ty);
+        ty.to_token_stream().to_string()
+    }
+
+    #[test]
+    fn erase_lifetimes_keeps_bound_lifetimes() {
+        assert_eq!(
+            erase_lifetimes(parse_quote!(&'a u8)),
+            quote!(&'_ u8).to_string()
+        );
+        assert_eq!(
+            erase_lifetimes(parse_quote!(for<'a> fn(&'a u8, &'b u8) -> &'a u8)),
+            quote!(for<'a> fn(&'a u8, &'_ u8) -> &'a u8).to_string()
+        );
+        assert_eq!(
+            erase_lifetimes(parse_quote!(&'a dyn for<'b> Fn(&'a u8, &'b u8))),
+            quote!(&'_ dyn for<'b> Fn(&'_ u8, &'b u8)).to_string()
+        );
+        // The binder is only in scope within the function pointer type.
+        assert_eq!(
+            erase_lifetimes(parse_quote!((for<'a> fn(&'a u8), &'a u8))),
+            quote!((for<'a> fn(&'a u8), &'_ u8)).to_string()
+        );
+    }
 }
@@ -39,6 +39,25 @@ cfg_if::cfg_if! {
         macro_rules! __asm_arch_suffix {
             () => { ".arm" };
         }
+    } else if #[cfg(any(
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "mips32r6",
+        target_arch = "mips64r6"
+    ))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_arch_prefix {
+            () => { ".set nomips16\n.set nomicromips" };
+            (mips::mips16) => { ".set nomicromips\n.set mips16" };
+            (mips::micromips) => { ".set nomips16\n.set micromips" };
+            ($isa:path) => { compile_error!("invalid instruction set") };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_arch_suffix {
+            () => { ".set nomips16\n.set nomicromips" };
+        }
     } else {
         #[doc(hidden)]
         #[macro_export]
@@ -53,49 +72,800 @@ cfg_if::cfg_if! {
         }
     }
 }
+// Target-specific options given as arguments to the `#[naked]` attribute, which
+// change the assembler state for the body of the function.
+cfg_if::cfg_if! {
+    if #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))] {
+        // The assembler state is saved and restored with `.option push` and
+        // `.option pop` so that the options only apply to this function.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_target_options_begin {
+            () => { "" };
+            ($(riscv::$option:ident),+) => {
+                concat!(".option push\n", $($crate::__asm_riscv_option!($option)),+)
+            };
+            ($($option:tt)*) => { compile_error!("invalid target option") };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_riscv_option {
+            (relax) => { ".option relax\n" };
+            (norelax) => { ".option norelax\n" };
+            (rvc) => { ".option rvc\n" };
+            (norvc) => { ".option norvc\n" };
+            ($option:ident) => {
+                compile_error!(concat!("unknown RISC-V option `", stringify!($option), "`"))
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_target_options_end {
+            () => { "" };
+            ($($option:tt)*) => { ".option pop\n" };
+        }
+    } else if #[cfg(any(
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "mips32r6",
+        target_arch = "mips64r6"
+    ))] {
+        // The assembler state is saved and restored with `.set push` and
+        // `.set pop` so that the options only apply to this function.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_target_options_begin {
+            () => { "" };
+            ($(mips::$option:ident),+) => {
+                concat!(".set push\n", $($crate::__asm_mips_option!($option)),+)
+            };
+            ($($option:tt)*) => { compile_error!("invalid target option") };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_mips_option {
+            (noreorder) => { ".set noreorder\n" };
+            (nomacro) => { ".set nomacro\n" };
+            (noat) => { ".set noat\n" };
+            ($option:ident) => {
+                compile_error!(concat!("unknown MIPS option `", stringify!($option), "`"))
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_target_options_end {
+            () => { "" };
+            ($($option:tt)*) => { ".set pop\n" };
+        }
+    } else if #[cfg(target_arch = "powerpc64")] {
+        // The TOC setup is part of the function entry, see __asm_function_label.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_target_options_begin {
+            () => { "" };
+            (powerpc64::toc) => { "" };
+            ($($option:tt)*) => { compile_error!("invalid target option") };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_target_options_end {
+            () => { "" };
+            ($($option:tt)*) => { "" };
+        }
+    } else if #[cfg(all(target_arch = "arm", not(any(target_vendor = "apple", windows))))] {
+        // The unwind annotations are emitted by __asm_ehabi_begin.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_target_options_begin {
+            () => { "" };
+            ($(arm::$option:ident($($arg:tt)*)),+) => { "" };
+            ($($option:tt)*) => { compile_error!("invalid target option") };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_target_options_end {
+            () => { "" };
+            ($($option:tt)*) => { "" };
+        }
+    } else if #[cfg(target_arch = "avr")] {
+        // The vector table in the avr-libc startup code jumps to `__vector_N`,
+        // which is a weak alias of `__bad_interrupt` unless it is defined.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_target_options_begin {
+            () => { "" };
+            (avr::vector($n:literal)) => {
+                concat!(
+                    ".globl __vector_", $n, "\n",
+                    ".type __vector_", $n, ", @function\n",
+                    "__vector_", $n, ":\n",
+                )
+            };
+            ($($option:tt)*) => { compile_error!("invalid target option") };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_target_options_end {
+            () => { "" };
+            ($($option:tt)*) => { "" };
+        }
+    } else if #[cfg(target_arch = "msp430")] {
+        // The linker scripts for MSP430 devices place the contents of the
+        // `__interrupt_vector_N` sections in the corresponding entries of the
+        // vector table.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_target_options_begin {
+            () => { "" };
+            (msp430::vector($n:literal)) => {
+                concat!(
+                    ".L__interrupt_vector_", $n, ":\n",
+                    ".pushsection __interrupt_vector_", $n, ",\"ax\",@progbits\n",
+                    ".word .L__interrupt_vector_", $n, "\n",
+                    ".popsection\n",
+                )
+            };
+            ($($option:tt)*) => { compile_error!("invalid target option") };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_target_options_end {
+            () => { "" };
+            ($($option:tt)*) => { "" };
+        }
+    } else {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_target_options_begin {
+            () => { "" };
+            ($($option:tt)*) => { compile_error!("invalid target option") };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_target_options_end {
+            () => { "" };
+            ($($option:tt)*) => { "" };
+        }
+    }
+}
+
+// Modifiers for `sym` operands, which wrap the placeholder for the symbol in
+// the template. Each `sym(modifier)` is expanded by the proc macro to a call
+// to `__asm_sym_<modifier>!`.
+cfg_if::cfg_if! {
+    if #[cfg(target_arch = "avr")] {
+        // Code addresses on AVR are word addresses, and `gs()` additionally
+        // generates a stub for functions beyond the first 128KiB of flash.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_pm {
+            ($placeholder:literal) => { concat!("pm(", $placeholder, ")") };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_gs {
+            ($placeholder:literal) => { concat!("gs(", $placeholder, ")") };
+        }
+    } else if #[cfg(target_arch = "msp430")] {
+        // Code and data share the same address space on MSP430.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_pm {
+            ($placeholder:literal) => { $placeholder };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_gs {
+            ($placeholder:literal) => { $placeholder };
+        }
+    } else {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_pm {
+            ($placeholder:literal) => {
+                compile_error!("`sym(pm)` operands are only supported on AVR and MSP430")
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_gs {
+            ($placeholder:literal) => {
+                compile_error!("`sym(gs)` operands are only supported on AVR and MSP430")
+            };
+        }
+    }
+}
+cfg_if::cfg_if! {
+    if #[cfg(all(target_arch = "x86", not(windows), not(target_vendor = "apple")))] {
+        // Position-independent code on 32-bit x86 ELF targets has no
+        // PC-relative addressing, so symbols are accessed relative to the GOT
+        // whose address is computed with the help of a PC thunk. The thunk is
+        // emitted in a COMDAT section so that only one copy is kept, and is
+        // encoded as raw bytes (mov ebx, [esp]; ret) so that it works with
+        // both Intel and AT&T syntax.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_got {
+            ($placeholder:literal) => { concat!($placeholder, "@GOT") };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_gotoff {
+            ($placeholder:literal) => { concat!($placeholder, "@GOTOFF") };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_pc_thunk {
+            () => {
+                concat!(
+                    ".pushsection .text.__x86.get_pc_thunk.bx,\"axG\",@progbits,__x86.get_pc_thunk.bx,comdat\n",
+                    ".globl __x86.get_pc_thunk.bx\n",
+                    ".hidden __x86.get_pc_thunk.bx\n",
+                    ".type __x86.get_pc_thunk.bx, @function\n",
+                    "__x86.get_pc_thunk.bx:\n",
+                    ".byte 0x8b, 0x1c, 0x24, 0xc3\n",
+                    ".size __x86.get_pc_thunk.bx, 4\n",
+                    ".popsection\n",
+                )
+            };
+        }
+    } else {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_got {
+            ($placeholder:literal) => {
+                compile_error!("`sym(got)` operands are only supported on 32-bit x86 ELF targets")
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_gotoff {
+            ($placeholder:literal) => {
+                compile_error!("`sym(gotoff)` operands are only supported on 32-bit x86 ELF targets")
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_pc_thunk {
+            () => { "" };
+        }
+    }
+}
+
+// Calls to functions which may be defined in a shared library must go through
+// the PLT. Other targets either use the PLT automatically for direct calls
+// (AArch64, ARM, RISC-V, LoongArch) or resolve calls to imported functions
+// through thunks in the import library (COFF).
+cfg_if::cfg_if! {
+    if #[cfg(all(
+        any(
+            target_arch = "x86",
+            target_arch = "x86_64",
+            target_arch = "s390x",
+            target_arch = "powerpc"
+        ),
+        not(windows),
+        not(target_vendor = "apple")
+    ))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_call {
+            ($placeholder:literal) => { concat!($placeholder, "@PLT") };
+        }
+    } else {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_call {
+            ($placeholder:literal) => { $placeholder };
+        }
+    }
+}
+
+// Thread-local variables are accessed with a sequence of instructions which
+// computes the address of the variable into a register, given by the format
+// spec of the placeholder or defaulting to the return value register.
+//
+// On ELF targets, `sym(tls)` uses the local-exec model, which is only valid in
+// executables, and `sym(tls_ie)` uses the initial-exec model, which loads the
+// offset of the variable from the GOT and is also valid in shared libraries
+// that are loaded at startup. Windows uses the TLS index of the module and
+// the thread's TLS array for both.
+cfg_if::cfg_if! {
+    if #[cfg(all(target_arch = "x86_64", windows))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls {
+            ($placeholder:literal) => { $crate::__asm_sym_tls!($placeholder, "rax") };
+            ($placeholder:literal, $reg:literal) => {
+                concat!(
+                    "movsxd ", $reg, ", dword ptr [rip + _tls_index]\n",
+                    "shl ", $reg, ", 3\n",
+                    "add ", $reg, ", qword ptr gs:[0x58]\n",
+                    "mov ", $reg, ", qword ptr [", $reg, "]\n",
+                    "lea ", $reg, ", [", $reg, " + ", $placeholder, "@SECREL32]",
+                )
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls_ie {
+            ($($args:tt)*) => { $crate::__asm_sym_tls!($($args)*) };
+        }
+    } else if #[cfg(all(target_arch = "x86", windows))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls {
+            ($placeholder:literal) => { $crate::__asm_sym_tls!($placeholder, "eax") };
+            ($placeholder:literal, $reg:literal) => {
+                concat!(
+                    "mov ", $reg, ", dword ptr [__tls_index]\n",
+                    "shl ", $reg, ", 2\n",
+                    "add ", $reg, ", dword ptr fs:[0x2c]\n",
+                    "mov ", $reg, ", dword ptr [", $reg, "]\n",
+                    "lea ", $reg, ", [", $reg, " + ", $placeholder, "@SECREL32]",
+                )
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls_ie {
+            ($($args:tt)*) => { $crate::__asm_sym_tls!($($args)*) };
+        }
+    } else if #[cfg(target_vendor = "apple")] {
+        // Mach-O thread-local variables are accessed by calling a function
+        // through their descriptor, which can't be done transparently.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls {
+            ($placeholder:literal $(, $reg:literal)?) => {
+                compile_error!("`sym(tls)` operands are not supported on Apple targets")
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls_ie {
+            ($placeholder:literal $(, $reg:literal)?) => {
+                compile_error!("`sym(tls_ie)` operands are not supported on Apple targets")
+            };
+        }
+    } else if #[cfg(target_arch = "x86_64")] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls {
+            ($placeholder:literal) => { $crate::__asm_sym_tls!($placeholder, "rax") };
+            ($placeholder:literal, $reg:literal) => {
+                concat!(
+                    "mov ", $reg, ", qword ptr fs:[0]\n",
+                    "lea ", $reg, ", [", $reg, " + ", $placeholder, "@TPOFF]",
+                )
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls_ie {
+            ($placeholder:literal) => { $crate::__asm_sym_tls_ie!($placeholder, "rax") };
+            ($placeholder:literal, $reg:literal) => {
+                concat!(
+                    "mov ", $reg, ", qword ptr [rip + ", $placeholder, "@GOTTPOFF]\n",
+                    "add ", $reg, ", qword ptr fs:[0]",
+                )
+            };
+        }
+    } else if #[cfg(target_arch = "x86")] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls {
+            ($placeholder:literal) => { $crate::__asm_sym_tls!($placeholder, "eax") };
+            ($placeholder:literal, $reg:literal) => {
+                concat!(
+                    "mov ", $reg, ", dword ptr gs:[0]\n",
+                    "lea ", $reg, ", [", $reg, " + ", $placeholder, "@NTPOFF]",
+                )
+            };
+        }
+        // The GOT entry is addressed relative to `ebx`, like calls through the
+        // PLT.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls_ie {
+            ($placeholder:literal) => { $crate::__asm_sym_tls_ie!($placeholder, "eax") };
+            ($placeholder:literal, $reg:literal) => {
+                concat!(
+                    "mov ", $reg, ", dword ptr gs:[0]\n",
+                    "add ", $reg, ", dword ptr [ebx + ", $placeholder, "@GOTNTPOFF]",
+                )
+            };
+        }
+    } else if #[cfg(target_arch = "aarch64")] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls {
+            ($placeholder:literal) => { $crate::__asm_sym_tls!($placeholder, "x0") };
+            ($placeholder:literal, $reg:literal) => {
+                concat!(
+                    "mrs ", $reg, ", tpidr_el0\n",
+                    "add ", $reg, ", ", $reg, ", #:tprel_hi12:", $placeholder, ", lsl #12\n",
+                    "add ", $reg, ", ", $reg, ", #:tprel_lo12_nc:", $placeholder,
+                )
+            };
+        }
+        // The thread pointer is read into `x17`, or `x16` if the address is
+        // computed into `x17`.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls_ie {
+            ($placeholder:literal) => { $crate::__asm_sym_tls_ie!($placeholder, "x0") };
+            ($placeholder:literal, "x17") => {
+                $crate::__asm_sym_tls_ie!(@tp $placeholder, "x17", "x16")
+            };
+            ($placeholder:literal, $reg:literal) => {
+                $crate::__asm_sym_tls_ie!(@tp $placeholder, $reg, "x17")
+            };
+            (@tp $placeholder:literal, $reg:literal, $tp:literal) => {
+                concat!(
+                    "adrp ", $reg, ", :gottprel:", $placeholder, "\n",
+                    "ldr ", $reg, ", [", $reg, ", #:gottprel_lo12:", $placeholder, "]\n",
+                    "mrs ", $tp, ", tpidr_el0\n",
+                    "add ", $reg, ", ", $reg, ", ", $tp,
+                )
+            };
+        }
+    } else if #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls {
+            ($placeholder:literal) => { $crate::__asm_sym_tls!($placeholder, "a0") };
+            ($placeholder:literal, $reg:literal) => {
+                concat!(
+                    "lui ", $reg, ", %tprel_hi(", $placeholder, ")\n",
+                    "add ", $reg, ", ", $reg, ", tp, %tprel_add(", $placeholder, ")\n",
+                    "addi ", $reg, ", ", $reg, ", %tprel_lo(", $placeholder, ")",
+                )
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls_ie {
+            ($placeholder:literal) => { $crate::__asm_sym_tls_ie!($placeholder, "a0") };
+            ($placeholder:literal, $reg:literal) => {
+                concat!(
+                    "la.tls.ie ", $reg, ", ", $placeholder, "\n",
+                    "add ", $reg, ", ", $reg, ", tp",
+                )
+            };
+        }
+    } else if #[cfg(target_arch = "loongarch64")] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls {
+            ($placeholder:literal) => { $crate::__asm_sym_tls!($placeholder, "$a0") };
+            ($placeholder:literal, $reg:literal) => {
+                concat!(
+                    "lu12i.w ", $reg, ", %le_hi20(", $placeholder, ")\n",
+                    "ori ", $reg, ", ", $reg, ", %le_lo12(", $placeholder, ")\n",
+                    "add.d ", $reg, ", ", $reg, ", $tp",
+                )
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls_ie {
+            ($placeholder:literal) => { $crate::__asm_sym_tls_ie!($placeholder, "$a0") };
+            ($placeholder:literal, $reg:literal) => {
+                concat!(
+                    "la.tls.ie ", $reg, ", ", $placeholder, "\n",
+                    "add.d ", $reg, ", ", $reg, ", $tp",
+                )
+            };
+        }
+    } else {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls {
+            ($placeholder:literal $(, $reg:literal)?) => {
+                compile_error!("`sym(tls)` operands are not supported on this target")
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_sym_tls_ie {
+            ($placeholder:literal $(, $reg:literal)?) => {
+                compile_error!("`sym(tls_ie)` operands are not supported on this target")
+            };
+        }
+    }
+}
+
+// Functions using the vectorcall convention are decorated as `name@@N` on all
+// targets, where N is the size of the arguments. On 32-bit Windows, stdcall
+// and fastcall functions are also decorated with the size of their arguments.
+//
+// The size is computed by the proc macro and passed as the
+// `__naked_function_arg_size` operand to `global_asm!`.
 cfg_if::cfg_if! {
-    if #[cfg(any(
-            target_vendor = "apple",
-            all(windows, target_arch = "x86"),
-        ))] {
+    if #[cfg(all(windows, target_arch = "x86"))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_mangle {
+            ($symbol:expr, "stdcall") => { concat!("_", $symbol, "@{__naked_function_arg_size}") };
+            ($symbol:expr, "fastcall") => { concat!("@", $symbol, "@{__naked_function_arg_size}") };
+            ($symbol:expr, "vectorcall") => { concat!($symbol, "@@{__naked_function_arg_size}") };
+            ($symbol:expr, $abi:tt) => { concat!("_", $symbol) };
+        }
+    } else if #[cfg(target_vendor = "apple")] {
         #[doc(hidden)]
         #[macro_export]
         macro_rules! __asm_mangle {
-            ($symbol:expr) => { concat!("_", $symbol) };
+            ($symbol:expr, "vectorcall") => { concat!("\"", $symbol, "@@{__naked_function_arg_size}\"") };
+            ($symbol:expr, $abi:tt) => { concat!("_", $symbol) };
+        }
+    } else if #[cfg(windows)] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_mangle {
+            ($symbol:expr, "vectorcall") => { concat!($symbol, "@@{__naked_function_arg_size}") };
+            ($symbol:expr, $abi:tt) => { $symbol };
         }
     } else {
+        // ELF uses @ for symbol versioning so the name needs to be quoted.
         #[doc(hidden)]
         #[macro_export]
         macro_rules! __asm_mangle {
-            ($symbol:expr) => { $symbol };
+            ($symbol:expr, "vectorcall") => { concat!("\"", $symbol, "@@{__naked_function_arg_size}\"") };
+            ($symbol:expr, $abi:tt) => { $symbol };
+        }
+    }
+}
+
+// asm! rejects unused operands, so the argument size operand must also be
+// referenced on targets which don't decorate symbols with it.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __asm_arg_size {
+    ("stdcall") => {
+        "/* {__naked_function_arg_size} */\n"
+    };
+    ("fastcall") => {
+        "/* {__naked_function_arg_size} */\n"
+    };
+    ("vectorcall") => {
+        "/* {__naked_function_arg_size} */\n"
+    };
+    ($abi:tt) => {
+        ""
+    };
+}
+
+/// Returns the number of bytes that an argument of type `T` contributes to the
+/// size used in stdcall, fastcall and vectorcall symbol decorations.
+#[doc(hidden)]
+pub const fn __arg_size<T>() -> usize {
+    let align = core::mem::size_of::<usize>();
+    (core::mem::size_of::<T>() + align - 1) / align * align
+}
+
+// Call frame information for ELF and Mach-O targets. This is emitted by
+// default for functions with an -unwind ABI. Any changes to the stack pointer
+// or saved registers must be described with .cfi_* directives in the function
+// body.
+cfg_if::cfg_if! {
+    if #[cfg(not(any(windows, target_family = "wasm")))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_cfi_begin {
+            () => { ".cfi_startproc\n" };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_cfi_end {
+            () => { ".cfi_endproc\n" };
+        }
+    } else {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_cfi_begin {
+            () => { "" };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_cfi_end {
+            () => { "" };
+        }
+    }
+}
+
+// Functions using the `entry_point` option mark the return address as
+// undefined in their call frame information, which tells debuggers and
+// unwinders that this is the outermost frame.
+cfg_if::cfg_if! {
+    if #[cfg(any(windows, target_family = "wasm"))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_entry_point {
+            () => { compile_error!("the `entry_point` option is not supported on this target") };
+        }
+    } else if #[cfg(target_arch = "x86_64")] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_entry_point {
+            () => { ".cfi_undefined rip\n" };
+        }
+    } else if #[cfg(target_arch = "x86")] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_entry_point {
+            () => { ".cfi_undefined eip\n" };
+        }
+    } else if #[cfg(any(
+        target_arch = "aarch64",
+        target_arch = "arm",
+        target_arch = "powerpc",
+        target_arch = "powerpc64"
+    ))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_entry_point {
+            () => { ".cfi_undefined lr\n" };
+        }
+    } else if #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_entry_point {
+            () => { ".cfi_undefined ra\n" };
+        }
+    } else if #[cfg(any(
+        target_arch = "loongarch64",
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "mips32r6",
+        target_arch = "mips64r6"
+    ))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_entry_point {
+            () => { ".cfi_undefined $ra\n" };
+        }
+    } else if #[cfg(target_arch = "s390x")] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_entry_point {
+            () => { ".cfi_undefined %r14\n" };
+        }
+    } else {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_entry_point {
+            () => { compile_error!("the `entry_point` option is not supported on this target") };
+        }
+    }
+}
+
+// Unwind info for functions using the `unwind_tables` option. On Windows the
+// function is registered in .pdata with .seh_proc/.seh_endproc, and the
+// prologue is described by .seh_* directives written in the function body.
+cfg_if::cfg_if! {
+    if #[cfg(all(windows, any(target_arch = "x86_64", target_arch = "aarch64")))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_unwind_begin {
+            ($symbol:expr, $abi:tt) => {
+                concat!(".seh_proc ", $crate::__asm_mangle!($symbol, $abi), "\n")
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_unwind_end {
+            () => { ".seh_endproc\n" };
+        }
+    } else if #[cfg(not(any(windows, target_family = "wasm")))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_unwind_begin {
+            ($symbol:expr, $abi:tt) => { $crate::__asm_cfi_begin!() };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_unwind_end {
+            () => { $crate::__asm_cfi_end!() };
+        }
+    } else {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_unwind_begin {
+            ($symbol:expr, $abi:tt) => {
+                compile_error!("the `unwind_tables` option is not supported on this target")
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_unwind_end {
+            () => { "" };
+        }
+    }
+}
+
+// ARM EHABI unwind tables for ELF targets. These are indexed by .ARM.exidx
+// entries generated from .fnstart/.fnend, which is also used to mark
+// functions that can't be unwound through with .cantunwind. The effect of the
+// prologue on the stack is described by the `arm::save`, `arm::vsave` and
+// `arm::pad` options.
+cfg_if::cfg_if! {
+    if #[cfg(all(target_arch = "arm", not(any(target_vendor = "apple", windows))))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_ehabi_begin {
+            (none, ($($option:tt)*)) => {
+                compile_error!("ARM unwind annotations can't be used with `no_unwind_tables`")
+            };
+            (cantunwind, ()) => { ".fnstart\n.cantunwind\n" };
+            (cantunwind, ($($option:tt)*)) => {
+                compile_error!("ARM unwind annotations require an `-unwind` ABI or `unwind_tables`")
+            };
+            (unwind, ($(arm::$option:ident($($arg:tt)*)),*)) => {
+                concat!(".fnstart\n", $($crate::__asm_ehabi_annotation!($option($($arg)*)),)*)
+            };
+            (unwind, ($($option:tt)*)) => { "" };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_ehabi_annotation {
+            (save($($reg:ident),+)) => { concat!(".save {", stringify!($($reg),+), "}\n") };
+            (vsave($($reg:ident),+)) => { concat!(".vsave {", stringify!($($reg),+), "}\n") };
+            (pad($size:literal)) => { concat!(".pad #", stringify!($size), "\n") };
+            ($option:ident($($arg:tt)*)) => {
+                compile_error!(concat!(
+                    "invalid ARM option `", stringify!($option), "(", stringify!($($arg)*), ")`"
+                ))
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_ehabi_end {
+            (none) => { "" };
+            ($mode:ident) => { ".fnend\n" };
+        }
+    } else {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_ehabi_begin {
+            ($mode:ident, ($($option:tt)*)) => { "" };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_ehabi_end {
+            ($mode:ident) => { "" };
         }
     }
 }
+
 cfg_if::cfg_if! {
     if #[cfg(windows)] {
         // COFF
         #[doc(hidden)]
         #[macro_export]
         macro_rules! __asm_function_begin {
-            ($symbol:expr, $section:expr, ($($instruction_set:tt)*)) => {
+            ($symbol:expr, $abi:tt, $section:expr, ($($instruction_set:tt)*), ($($option:tt)*)) => {
                 concat!(
+                    $crate::__asm_arg_size!($abi),
                     ".pushsection ", $section, ",\"xr\"\n",
                     ".balign 4\n",
-                    ".globl ", $crate::__asm_mangle!($symbol), "\n",
-                    ".def ", $crate::__asm_mangle!($symbol), "\n",
+                    ".globl ", $crate::__asm_mangle!($symbol, $abi), "\n",
+                    ".def ", $crate::__asm_mangle!($symbol, $abi), "\n",
                     ".scl 2\n",
                     ".type 32\n",
-                    ".endef ", $crate::__asm_mangle!($symbol), "\n",
+                    ".endef ", $crate::__asm_mangle!($symbol, $abi), "\n",
                     $crate::__asm_arch_prefix!($($instruction_set)*), "\n",
-                    $crate::__asm_mangle!($symbol), ":\n",
+                    $crate::__asm_mangle!($symbol, $abi), ":\n",
+                    $crate::__asm_target_options_begin!($($option)*),
                 )
             };
         }
         #[doc(hidden)]
         #[macro_export]
         macro_rules! __asm_function_end {
-            ($symbol:expr) => {
+            ($symbol:expr, $abi:tt, ($($option:tt)*)) => {
                 concat!(
+                    $crate::__asm_target_options_end!($($option)*),
                     ".popsection\n",
                     $crate::__asm_arch_suffix!(), "\n",
                 )
@@ -111,22 +881,25 @@ cfg_if::cfg_if! {
         #[doc(hidden)]
         #[macro_export]
         macro_rules! __asm_function_begin {
-            ($symbol:expr, $section:expr, ($($instruction_set:tt)*)) => {
+            ($symbol:expr, $abi:tt, $section:expr, ($($instruction_set:tt)*), ($($option:tt)*)) => {
                 concat!(
+                    $crate::__asm_arg_size!($abi),
                     ".pushsection ", $section, ",regular,pure_instructions\n",
                     ".balign 4\n",
-                    ".globl ", $crate::__asm_mangle!($symbol), "\n",
-                    ".private_extern ", $crate::__asm_mangle!($symbol), "\n",
+                    ".globl ", $crate::__asm_mangle!($symbol, $abi), "\n",
+                    ".private_extern ", $crate::__asm_mangle!($symbol, $abi), "\n",
                     $crate::__asm_arch_prefix!($($instruction_set)*), "\n",
-                    $crate::__asm_mangle!($symbol), ":\n",
+                    $crate::__asm_mangle!($symbol, $abi), ":\n",
+                    $crate::__asm_target_options_begin!($($option)*),
                 )
             };
         }
         #[doc(hidden)]
         #[macro_export]
         macro_rules! __asm_function_end {
-            ($symbol:expr) => {
+            ($symbol:expr, $abi:tt, ($($option:tt)*)) => {
                 concat!(
+                    $crate::__asm_target_options_end!($($option)*),
                     ".popsection\n",
                     $crate::__asm_arch_suffix!(), "\n",
                 )
@@ -137,6 +910,39 @@ cfg_if::cfg_if! {
         macro_rules! __asm_default_section {
             ($symbol:expr) => { "__TEXT,__text" };
         }
+    } else if #[cfg(target_family = "wasm")] {
+        // WebAssembly object files don't support .pushsection/.popsection,
+        // and functions must be terminated with end_function.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_function_begin {
+            ($symbol:expr, $abi:tt, $section:expr, ($($instruction_set:tt)*), ($($option:tt)*)) => {
+                concat!(
+                    ".section ", $section, ",\"\",@\n",
+                    ".globl ", $crate::__asm_mangle!($symbol, $abi), "\n",
+                    ".hidden ", $crate::__asm_mangle!($symbol, $abi), "\n",
+                    ".type ", $crate::__asm_mangle!($symbol, $abi), ",@function\n",
+                    $crate::__asm_arch_prefix!($($instruction_set)*), "\n",
+                    $crate::__asm_mangle!($symbol, $abi), ":\n",
+                    $crate::__asm_target_options_begin!($($option)*),
+                )
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_function_end {
+            ($symbol:expr, $abi:tt, ($($option:tt)*)) => {
+                concat!(
+                    $crate::__asm_target_options_end!($($option)*),
+                    "end_function\n",
+                )
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_default_section {
+            ($symbol:expr) => { concat!(".text.", $symbol) };
+        }
     } else {
         // Everything else uses ELF. ARM uses % instead of @ for some
         // assembler directives.
@@ -152,27 +958,203 @@ cfg_if::cfg_if! {
         macro_rules! __asm_type {
             ($ty:literal) => { concat!("%", $ty) }
         }
+        // Arm CMSE secure entry functions need a second `__acle_se_` symbol at
+        // the same address, which instructs the linker to generate a secure
+        // gateway veneer for the function in `.gnu.sgstubs`. These must not be
+        // hidden so that they are exported in the secure import library.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_visibility {
+            ($symbol:expr, "C-cmse-nonsecure-entry") => { "" };
+            ($symbol:expr, "cmse-nonsecure-entry") => { "" };
+            ($symbol:expr, $abi:tt) => { concat!(".hidden ", $symbol, "\n") };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_secure_entry_begin {
+            ($symbol:expr, "C-cmse-nonsecure-entry") => {
+                $crate::__asm_secure_entry_begin!($symbol, "cmse-nonsecure-entry")
+            };
+            ($symbol:expr, "cmse-nonsecure-entry") => {
+                concat!(
+                    ".globl __acle_se_", $symbol, "\n",
+                    ".type __acle_se_", $symbol, ", %function\n",
+                    ".thumb_func\n",
+                    "__acle_se_", $symbol, ":\n",
+                )
+            };
+            ($symbol:expr, $abi:tt) => { "" };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_secure_entry_end {
+            ($symbol:expr, "C-cmse-nonsecure-entry") => {
+                $crate::__asm_secure_entry_end!($symbol, "cmse-nonsecure-entry")
+            };
+            ($symbol:expr, "cmse-nonsecure-entry") => {
+                concat!(".size __acle_se_", $symbol, ", . - __acle_se_", $symbol, "\n")
+            };
+            ($symbol:expr, $abi:tt) => { "" };
+        }
+        cfg_if::cfg_if! {
+            if #[cfg(all(
+                target_arch = "powerpc64",
+                target_endian = "big",
+                target_os = "linux",
+                target_env = "gnu"
+            ))] {
+                // The PPC64 ELFv1 ABI uses function descriptors: the function
+                // symbol points to a descriptor in .opd containing the address
+                // of the code and the TOC pointer, which callers load into r2.
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_label {
+                    ($symbol:expr, ($($option:tt)*)) => {
+                        concat!(
+                            ".pushsection .opd, \"aw\"\n",
+                            ".balign 8\n",
+                            $symbol, ":\n",
+                            ".quad .L.", $symbol, ", .TOC.@tocbase, 0\n",
+                            ".popsection\n",
+                            ".L.", $symbol, ":\n",
+                        )
+                    };
+                }
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_start {
+                    ($symbol:expr) => { concat!(".L.", $symbol) };
+                }
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_label_end {
+                    ($symbol:expr) => { "" };
+                }
+            } else if #[cfg(target_arch = "powerpc64")] {
+                // The PPC64 ELFv2 ABI has a global entry point, which is called
+                // with the function address in r12 and sets up the TOC pointer
+                // in r2, followed by a local entry point used by callers which
+                // share the same TOC.
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_label {
+                    ($symbol:expr, (powerpc64::toc)) => {
+                        concat!(
+                            $symbol, ":\n",
+                            "addis 2, 12, .TOC. - ", $symbol, "@ha\n",
+                            "addi 2, 2, .TOC. - ", $symbol, "@l\n",
+                            ".localentry ", $symbol, ", . - ", $symbol, "\n",
+                        )
+                    };
+                    ($symbol:expr, ($($option:tt)*)) => { concat!($symbol, ":\n") };
+                }
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_start {
+                    ($symbol:expr) => { $symbol };
+                }
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_label_end {
+                    ($symbol:expr) => { "" };
+                }
+            } else if #[cfg(any(
+                target_arch = "mips",
+                target_arch = "mips64",
+                target_arch = "mips32r6",
+                target_arch = "mips64r6"
+            ))] {
+                // MIPS assemblers expect functions to be bracketed with .ent
+                // and .end.
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_label {
+                    ($symbol:expr, ($($option:tt)*)) => {
+                        concat!(".ent ", $symbol, "\n", $symbol, ":\n")
+                    };
+                }
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_start {
+                    ($symbol:expr) => { $symbol };
+                }
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_label_end {
+                    ($symbol:expr) => { concat!(".end ", $symbol, "\n") };
+                }
+            } else {
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_label {
+                    ($symbol:expr, ($($option:tt)*)) => { concat!($symbol, ":\n") };
+                }
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_start {
+                    ($symbol:expr) => { $symbol };
+                }
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_label_end {
+                    ($symbol:expr) => { "" };
+                }
+            }
+        }
+        cfg_if::cfg_if! {
+            if #[cfg(target_arch = "s390x")] {
+                // s390x only requires instructions to be 2-byte aligned, but
+                // function entry points are 16-byte aligned to match the code
+                // generated by LLVM.
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_align {
+                    () => { "16" };
+                }
+            } else if #[cfg(any(target_arch = "avr", target_arch = "msp430"))] {
+                // AVR and MSP430 instructions are made of 16-bit words.
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_align {
+                    () => { "2" };
+                }
+            } else {
+                // Other targets such as LoongArch use fixed 4-byte
+                // instructions.
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_function_align {
+                    () => { "4" };
+                }
+            }
+        }
         #[doc(hidden)]
         #[macro_export]
         macro_rules! __asm_function_begin {
-            ($symbol:expr, $section:expr, ($($instruction_set:tt)*)) => {
+            ($symbol:expr, $abi:tt, $section:expr, ($($instruction_set:tt)*), ($($option:tt)*)) => {
                 concat!(
+                    $crate::__asm_arg_size!($abi),
                     ".pushsection ", $section, ",\"ax\", ", $crate::__asm_type!("progbits"), "\n",
-                    ".balign 4\n",
-                    ".globl ", $crate::__asm_mangle!($symbol), "\n",
-                    ".hidden ", $crate::__asm_mangle!($symbol), "\n",
-                    ".type ", $crate::__asm_mangle!($symbol), ", ", $crate::__asm_type!("function"), "\n",
+                    ".balign ", $crate::__asm_function_align!(), "\n",
+                    ".globl ", $crate::__asm_mangle!($symbol, $abi), "\n",
+                    $crate::__asm_visibility!($crate::__asm_mangle!($symbol, $abi), $abi),
+                    ".type ", $crate::__asm_mangle!($symbol, $abi), ", ", $crate::__asm_type!("function"), "\n",
                     $crate::__asm_arch_prefix!($($instruction_set)*), "\n",
-                    $crate::__asm_mangle!($symbol), ":\n",
+                    $crate::__asm_function_label!($crate::__asm_mangle!($symbol, $abi), ($($option)*)),
+                    $crate::__asm_secure_entry_begin!($crate::__asm_mangle!($symbol, $abi), $abi),
+                    $crate::__asm_target_options_begin!($($option)*),
                 )
             };
         }
         #[doc(hidden)]
         #[macro_export]
         macro_rules! __asm_function_end {
-            ($symbol:expr) => {
+            ($symbol:expr, $abi:tt, ($($option:tt)*)) => {
                 concat!(
-                    ".size ", $crate::__asm_mangle!($symbol), ", . - ", $crate::__asm_mangle!($symbol), "\n",
+                    $crate::__asm_target_options_end!($($option)*),
+                    $crate::__asm_function_label_end!($crate::__asm_mangle!($symbol, $abi)),
+                    ".size ", $crate::__asm_mangle!($symbol, $abi), ", . - ", $crate::__asm_function_start!($crate::__asm_mangle!($symbol, $abi)), "\n",
+                    $crate::__asm_secure_entry_end!($crate::__asm_mangle!($symbol, $abi), $abi),
                     ".popsection\n",
                     $crate::__asm_arch_suffix!(), "\n",
                 )
@@ -185,3 +1167,227 @@ cfg_if::cfg_if! {
         }
     }
 }
+
+// Line number information for functions using the `debug_lines` option.
+//
+// Each function gets its own DWARF compile unit with a subprogram covering the
+// function, and a line table which maps the labels placed before each template
+// line by the proc macro to the source lines of the templates.
+//
+// The compiler's own compile unit expects its abbreviations to be at the start
+// of the abbreviation section, and the assembly of naked functions comes
+// before anything the compiler emits in the object file. The tables of each
+// function are therefore placed in separate sections with the same names as
+// the ones used by the compiler, which the linker concatenates. These are
+// created with an arbitrary `unique` ID which is too large to clash with the
+// ones LLVM numbers from 1 for its own sections.
+//
+// Mach-O doesn't support multiple sections with the same name, and compile
+// units placed before the compiler's own ones would invalidate the offsets in
+// its accelerator tables, so this isn't supported there.
+cfg_if::cfg_if! {
+    if #[cfg(any(windows, target_vendor = "apple", target_family = "wasm"))] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_debug_lines {
+            ($($tt:tt)*) => {
+                compile_error!("the `debug_lines` option is only supported on ELF targets")
+            };
+        }
+    } else {
+        cfg_if::cfg_if! {
+            if #[cfg(target_pointer_width = "64")] {
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_dwarf_address {
+                    ($label:expr) => { concat!(".quad ", $label, "\n") };
+                }
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_dwarf_address_size {
+                    () => { "8" };
+                }
+            } else if #[cfg(target_pointer_width = "32")] {
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_dwarf_address {
+                    ($label:expr) => { concat!(".long ", $label, "\n") };
+                }
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_dwarf_address_size {
+                    () => { "4" };
+                }
+            } else {
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_dwarf_address {
+                    ($label:expr) => {
+                        compile_error!("the `debug_lines` option is not supported on this target")
+                    };
+                }
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! __asm_dwarf_address_size {
+                    () => { "0" };
+                }
+            }
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_debug_section {
+            ($name:literal) => {
+                concat!(
+                    ".pushsection ", $name, ",\"\",", $crate::__asm_type!("progbits"),
+                    ",unique,1852534373\n"
+                )
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_debug_lines {
+            (
+                $prefix:literal, $name:literal, $file:literal,
+                $(($row:literal, $advance:literal, $column:literal)),*
+            ) => {
+                concat!(
+                    // Abbreviation table with entries for the compile unit,
+                    // with code 1, and for the subprogram, with code 2.
+                    $crate::__asm_debug_section!(".debug_abbrev"),
+                    $prefix, "_abbrev:\n",
+                    // DW_TAG_compile_unit, DW_CHILDREN_yes
+                    ".uleb128 1\n.uleb128 0x11\n.byte 1\n",
+                    // DW_AT_name, DW_FORM_string
+                    ".uleb128 0x03\n.uleb128 0x08\n",
+                    // DW_AT_language, DW_FORM_data2
+                    ".uleb128 0x13\n.uleb128 0x05\n",
+                    // DW_AT_stmt_list, DW_FORM_sec_offset
+                    ".uleb128 0x10\n.uleb128 0x17\n",
+                    // DW_AT_low_pc, DW_FORM_addr
+                    ".uleb128 0x11\n.uleb128 0x01\n",
+                    // DW_AT_high_pc, DW_FORM_data4
+                    ".uleb128 0x12\n.uleb128 0x06\n",
+                    ".byte 0\n.byte 0\n",
+                    // DW_TAG_subprogram, DW_CHILDREN_no
+                    ".uleb128 2\n.uleb128 0x2e\n.byte 0\n",
+                    // DW_AT_name, DW_FORM_string
+                    ".uleb128 0x03\n.uleb128 0x08\n",
+                    // DW_AT_low_pc, DW_FORM_addr
+                    ".uleb128 0x11\n.uleb128 0x01\n",
+                    // DW_AT_high_pc, DW_FORM_data4
+                    ".uleb128 0x12\n.uleb128 0x06\n",
+                    ".byte 0\n.byte 0\n",
+                    ".byte 0\n",
+                    ".popsection\n",
+
+                    // DWARF 4 compile unit, with DW_LANG_Mips_Assembler as the
+                    // language, containing the subprogram.
+                    $crate::__asm_debug_section!(".debug_info"),
+                    ".long ", $prefix, "_info_end - ", $prefix, "_info_start\n",
+                    $prefix, "_info_start:\n",
+                    ".short 4\n",
+                    ".long ", $prefix, "_abbrev\n",
+                    ".byte ", $crate::__asm_dwarf_address_size!(), "\n",
+                    ".uleb128 1\n",
+                    ".asciz \"", $file, "\"\n",
+                    ".short 0x8001\n",
+                    ".long ", $prefix, "_line\n",
+                    $crate::__asm_dwarf_address!(concat!($prefix, "_start")),
+                    ".long ", $prefix, "_end - ", $prefix, "_start\n",
+                    ".uleb128 2\n",
+                    ".asciz \"", $name, "\"\n",
+                    $crate::__asm_dwarf_address!(concat!($prefix, "_start")),
+                    ".long ", $prefix, "_end - ", $prefix, "_start\n",
+                    ".byte 0\n",
+                    $prefix, "_info_end:\n",
+                    ".popsection\n",
+
+                    // DWARF 4 line table header with the standard opcodes and
+                    // a single file.
+                    $crate::__asm_debug_section!(".debug_line"),
+                    $prefix, "_line:\n",
+                    ".long ", $prefix, "_line_end - ", $prefix, "_line_start\n",
+                    $prefix, "_line_start:\n",
+                    ".short 4\n",
+                    ".long ", $prefix, "_line_program - ", $prefix, "_line_header\n",
+                    $prefix, "_line_header:\n",
+                    ".byte 1\n.byte 1\n.byte 1\n.byte -5\n.byte 14\n.byte 13\n",
+                    ".byte 0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1\n",
+                    ".byte 0\n",
+                    ".asciz \"", $file, "\"\n",
+                    ".uleb128 0\n.uleb128 0\n.uleb128 0\n",
+                    ".byte 0\n",
+
+                    // Each row sets the address, advances the line, sets the
+                    // column and appends a row to the table.
+                    $prefix, "_line_program:\n",
+                    $(
+                        ".byte 0\n.uleb128 1 + ", $crate::__asm_dwarf_address_size!(), "\n.byte 2\n",
+                        $crate::__asm_dwarf_address!($row),
+                        ".byte 3\n.sleb128 ", $advance, "\n",
+                        ".byte 5\n.uleb128 ", $column, "\n",
+                        ".byte 1\n",
+                    )*
+                    ".byte 0\n.uleb128 1 + ", $crate::__asm_dwarf_address_size!(), "\n.byte 2\n",
+                    $crate::__asm_dwarf_address!(concat!($prefix, "_end")),
+                    ".byte 0\n.uleb128 1\n.byte 1\n",
+                    $prefix, "_line_end:\n",
+                    ".popsection\n",
+                )
+            };
+        }
+    }
+}
+
+// The WebAssembly assembler requires the type of each function to be declared
+// with .functype. The value types are computed by the proc macro from the
+// function signature.
+cfg_if::cfg_if! {
+    if #[cfg(target_family = "wasm")] {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_functype {
+            ($symbol:expr, ($($param:ident),*), ($($result:ident)?)) => {
+                concat!(
+                    ".functype ", $symbol,
+                    " (", $crate::__asm_wasm_types!($($param),*), ")",
+                    " -> (", $crate::__asm_wasm_types!($($result)?), ")\n",
+                )
+            };
+        }
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_wasm_types {
+            () => { "" };
+            ($first:ident $(, $rest:ident)*) => {
+                concat!($crate::__asm_wasm_type!($first) $(, ", ", $crate::__asm_wasm_type!($rest))*)
+            };
+        }
+        #[cfg(target_arch = "wasm64")]
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_wasm_type {
+            (ptr) => { "i64" };
+            (unknown) => {
+                compile_error!("unsupported type in the signature of a WebAssembly naked function")
+            };
+            ($ty:ident) => { stringify!($ty) };
+        }
+        #[cfg(not(target_arch = "wasm64"))]
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_wasm_type {
+            (ptr) => { "i32" };
+            (unknown) => {
+                compile_error!("unsupported type in the signature of a WebAssembly naked function")
+            };
+            ($ty:ident) => { stringify!($ty) };
+        }
+    } else {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! __asm_functype {
+            ($symbol:expr, ($($param:ident),*), ($($result:ident)?)) => { "" };
+        }
+    }
+}
@@ -1,5 +1,4 @@
 #![cfg(target_arch = "aarch64")]
-#![feature(asm_const)]
 
 #[naked_function::naked]
 pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
@@ -53,6 +52,43 @@ fn test_sym() {
     assert_eq!(str, "world");
 }
 
+// Apple targets pass variadic arguments on the stack.
+#[cfg(not(target_vendor = "apple"))]
+#[naked_function::naked]
+pub unsafe extern "C" fn sum(n: i32, args: ...) -> i32 {
+    asm!(
+        "mov w8, w0",
+        "mov w0, wzr",
+        "cbz w8, 2f",
+        "add w0, w0, w1",
+        "subs w8, w8, 1",
+        "b.eq 2f",
+        "add w0, w0, w2",
+        "subs w8, w8, 1",
+        "b.eq 2f",
+        "add w0, w0, w3",
+        "subs w8, w8, 1",
+        "b.eq 2f",
+        "add w0, w0, w4",
+        "subs w8, w8, 1",
+        "b.eq 2f",
+        "add w0, w0, w5",
+        "2:",
+        "ret",
+    );
+}
+
+#[cfg(not(target_vendor = "apple"))]
+#[test]
+fn test_variadic() {
+    unsafe {
+        assert_eq!(sum(0), 0);
+        assert_eq!(sum(1, 5), 5);
+        assert_eq!(sum(3, 1, 2, 3), 6);
+        assert_eq!(sum(5, 1, 2, 3, 4, 5), 15);
+    }
+}
+
 #[naked_function::naked]
 #[export_name = "exported_symbol_name"]
 pub unsafe extern "C" fn export_name() -> i32 {
@@ -88,3 +124,18 @@ fn test_mangled() {
     let val = unsafe { mangled() };
     assert_eq!(val, 5);
 }
+
+mod duplicate {
+    #[naked_function::naked]
+    pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+        asm!("add w0, w0, w1", "add w0, w0, 1", "ret");
+    }
+}
+
+#[test]
+fn test_duplicate_name() {
+    let ret = unsafe { duplicate::add(1, 2) };
+    assert_eq!(ret, 4);
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
@@ -1,5 +1,4 @@
 #![cfg(target_arch = "arm")]
-#![feature(asm_const)]
 
 use std::assert_eq;
 
@@ -90,6 +89,21 @@ fn test_mangled() {
     assert_eq!(val, 5);
 }
 
+mod duplicate {
+    #[naked_function::naked]
+    pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+        asm!("add r0, r0, r1", "add r0, r0, #1", "bx lr");
+    }
+}
+
+#[test]
+fn test_duplicate_name() {
+    let ret = unsafe { duplicate::add(1, 2) };
+    assert_eq!(ret, 4);
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
+
 #[naked_function::naked]
 #[instruction_set(arm::a32)]
 pub unsafe extern "C" fn add_arm(a: i32, b: i32) -> i32 {
@@ -115,3 +129,29 @@ fn test_thumb() {
     assert_eq!(ret, 3);
     assert_eq!(add_thumb as usize & 1, 1);
 }
+
+#[cfg(target_os = "linux")]
+#[naked_function::naked(arm::save(r4, lr), arm::pad(8))]
+pub unsafe extern "C-unwind" fn trampoline(f: extern "C-unwind" fn()) {
+    asm!(
+        "push {{r4, lr}}",
+        ".cfi_def_cfa_offset 8",
+        ".cfi_offset lr, -4",
+        ".cfi_offset r4, -8",
+        "sub sp, sp, #8",
+        ".cfi_def_cfa_offset 16",
+        "blx r0",
+        "add sp, sp, #8",
+        "pop {{r4, pc}}",
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_ehabi_unwind() {
+    extern "C-unwind" fn panics() {
+        panic!("unwinding through a naked function");
+    }
+    let result = std::panic::catch_unwind(|| unsafe { trampoline(panics) });
+    assert!(result.is_err());
+}
@@ -0,0 +1,64 @@
+#!/bin/bash
+# Checks the symbols, sections and unwind info emitted for the naked functions
+# of the `embedded` crate.
+#
+# Usage: check.sh <target> <path to the built rlib>
+set -euo pipefail
+
+target=$1
+lib=$2
+
+# Use the LLVM tools shipped with the toolchain, which support all targets.
+host=$(rustc -vV | sed -n 's/^host: //p')
+bin=$(rustc --print sysroot)/lib/rustlib/$host/bin
+
+# Fails unless the output of an LLVM tool contains a line matching a pattern.
+expect() {
+    local pattern=$1
+    shift
+    if ! "$bin/$1" "${@:2}" "$lib" | grep -E -- "$pattern" >/dev/null; then
+        echo "error: \`$*\` doesn't match \`$pattern\`" >&2
+        exit 1
+    fi
+}
+
+case $target in
+avr-none)
+    # The handler is aliased to the symbol used by the avr-libc vector table.
+    expect ' T __vector_3$' llvm-nm
+    # `sym(gs)` and `sym(pm)` operands use word address relocations.
+    expect 'R_AVR_LO8_LDI_GS[[:space:]]+.*5timer' llvm-objdump -dr
+    expect 'R_AVR_HI8_LDI_GS[[:space:]]+.*5timer' llvm-objdump -dr
+    expect 'R_AVR_16_PM[[:space:]]+.*5timer' llvm-objdump -dr
+    ;;
+msp430-none-elf)
+    # The handler address is placed in the vector table section.
+    expect '__interrupt_vector_5 +PROGBITS .* 000002 ' llvm-readelf -S
+    expect 'R_MSP430_16_BYTE[[:space:]]+\.text\..*5timer' llvm-objdump -dr -j __interrupt_vector_5
+    # `sym(gs)` and `sym(pm)` operands reference the handler directly.
+    expect '00000002: +R_MSP430_16_BYTE[[:space:]]+.*5timer' llvm-objdump -dr
+    expect '00000008: +R_MSP430_16_BYTE[[:space:]]+.*5timer' llvm-objdump -dr
+    ;;
+thumbv8m.main-none-eabi)
+    # The secure entry function has a second `__acle_se_` symbol. Both are
+    # exported Thumb functions, which have the lowest bit of their address set.
+    for symbol in secure_add __acle_se_secure_add; do
+        expect "[13579bdf] +4 FUNC +GLOBAL +DEFAULT +[0-9]+ $symbol$" llvm-readelf -s
+    done
+    ;;
+x86_64-pc-windows-msvc)
+    # The function is registered in .pdata, with the prologue described by the
+    # SEH directives in its body.
+    expect 'StartAddress: .*10trampoline' llvm-readobj --unwind
+    expect 'ALLOC_SMALL size=32' llvm-readobj --unwind
+    expect 'PUSH_NONVOL reg=RBP' llvm-readobj --unwind
+    ;;
+aarch64-pc-windows-msvc)
+    expect 'Function: .*10trampoline' llvm-readobj --unwind
+    expect '; stp x29, x30, \[sp, #-16\]!' llvm-readobj --unwind
+    ;;
+*)
+    echo "error: unknown target $target" >&2
+    exit 1
+    ;;
+esac
@@ -0,0 +1,18 @@
+#[naked_function::naked(avr::vector(3))]
+pub unsafe extern "avr-interrupt" fn timer() {
+    asm!("reti");
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn call_timer() {
+    asm!(
+        "ldi r30, lo8({handler})",
+        "ldi r31, hi8({handler})",
+        "icall",
+        "ret",
+        // Handler table in program memory.
+        ".word {0}",
+        sym(pm) timer,
+        handler = sym(gs) timer,
+    );
+}
@@ -0,0 +1,20 @@
+//! Naked functions for targets which can't run the tests in CI, such as
+//! targets without `std`. This crate is only built for these targets, after
+//! which `check.sh` checks the symbols, sections and unwind info emitted for
+//! the functions.
+#![no_std]
+#![feature(
+    abi_avr_interrupt,
+    abi_msp430_interrupt,
+    asm_experimental_arch,
+    cmse_nonsecure_entry
+)]
+
+#[cfg(target_arch = "avr")]
+pub mod avr;
+#[cfg(target_arch = "arm")]
+pub mod cmse;
+#[cfg(target_arch = "msp430")]
+pub mod msp430;
+#[cfg(windows)]
+pub mod seh;
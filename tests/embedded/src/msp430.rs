@@ -0,0 +1,17 @@
+#[naked_function::naked(msp430::vector(5))]
+pub unsafe extern "msp430-interrupt" fn timer() {
+    asm!("reti");
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn call_timer() {
+    asm!(
+        "mov #{handler}, r12",
+        "call r12",
+        "ret",
+        // Handler table.
+        ".word {0}",
+        sym(pm) timer,
+        handler = sym(gs) timer,
+    );
+}
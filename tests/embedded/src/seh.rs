@@ -0,0 +1,31 @@
+#[cfg(target_arch = "x86_64")]
+#[naked_function::naked(unwind_tables)]
+pub unsafe extern "C-unwind" fn trampoline(f: extern "C-unwind" fn()) {
+    asm!(
+        "push rbp",
+        ".seh_pushreg rbp",
+        "sub rsp, 32",
+        ".seh_stackalloc 32",
+        ".seh_endprologue",
+        "call rcx",
+        "add rsp, 32",
+        "pop rbp",
+        "ret",
+    );
+}
+
+#[cfg(target_arch = "aarch64")]
+#[naked_function::naked(unwind_tables)]
+pub unsafe extern "C-unwind" fn trampoline(f: extern "C-unwind" fn()) {
+    asm!(
+        "stp x29, x30, [sp, #-16]!",
+        ".seh_save_fplr_x 16",
+        ".seh_endprologue",
+        "blr x0",
+        ".seh_startepilogue",
+        "ldp x29, x30, [sp], #16",
+        ".seh_save_fplr_x 16",
+        ".seh_endepilogue",
+        "ret",
+    );
+}
@@ -0,0 +1,34 @@
+#![cfg(all(feature = "nightly", target_arch = "x86_64"))]
+#![feature(abi_x86_interrupt)]
+
+#[repr(C)]
+pub struct InterruptStackFrame {
+    pub rip: u64,
+    pub cs: u64,
+    pub rflags: u64,
+    pub rsp: u64,
+    pub ss: u64,
+}
+
+#[naked_function::naked]
+pub unsafe extern "x86-interrupt" fn breakpoint(frame: InterruptStackFrame) {
+    asm!("iretq");
+}
+
+#[naked_function::naked]
+pub unsafe extern "x86-interrupt" fn page_fault(frame: InterruptStackFrame, error_code: u64) {
+    asm!("add rsp, 8", "iretq");
+}
+
+#[test]
+fn test_handler_address() {
+    // The address of a handler is installed in the IDT, so it must point
+    // directly at the handler code without any prologue.
+    let code =
+        |handler: usize, len| unsafe { std::slice::from_raw_parts(handler as *const u8, len) };
+    assert_eq!(code(breakpoint as *const () as usize, 2), [0x48, 0xcf]);
+    assert_eq!(
+        code(page_fault as *const () as usize, 6),
+        [0x48, 0x83, 0xc4, 0x08, 0x48, 0xcf]
+    );
+}
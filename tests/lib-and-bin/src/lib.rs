@@ -0,0 +1,69 @@
+//! A library and a binary which both define a naked function with the same
+//! name, to check that their default symbols don't collide.
+#![cfg_attr(target_arch = "mips", feature(asm_experimental_arch))]
+
+// Assembly code which returns `$value` from a function, for each of the
+// targets the tests run on.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[macro_export]
+macro_rules! return_value {
+    ($value:literal) => {
+        concat!("mov eax, ", $value, "\nret")
+    };
+}
+#[cfg(target_arch = "aarch64")]
+#[macro_export]
+macro_rules! return_value {
+    ($value:literal) => {
+        concat!("mov w0, ", $value, "\nret")
+    };
+}
+#[cfg(target_arch = "arm")]
+#[macro_export]
+macro_rules! return_value {
+    ($value:literal) => {
+        concat!("mov r0, #", $value, "\nbx lr")
+    };
+}
+#[cfg(target_arch = "riscv64")]
+#[macro_export]
+macro_rules! return_value {
+    ($value:literal) => {
+        concat!("li a0, ", $value, "\nret")
+    };
+}
+#[cfg(target_arch = "powerpc64")]
+#[macro_export]
+macro_rules! return_value {
+    ($value:literal) => {
+        concat!("li 3, ", $value, "\nblr")
+    };
+}
+#[cfg(target_arch = "mips")]
+#[macro_export]
+macro_rules! return_value {
+    ($value:literal) => {
+        concat!("li $2, ", $value, "\njr $31")
+    };
+}
+#[cfg(target_arch = "loongarch64")]
+#[macro_export]
+macro_rules! return_value {
+    ($value:literal) => {
+        concat!("li.w $a0, ", $value, "\nret")
+    };
+}
+#[cfg(target_arch = "s390x")]
+#[macro_export]
+macro_rules! return_value {
+    ($value:literal) => {
+        concat!("lghi %r2, ", $value, "\nbr %r14")
+    };
+}
+
+// Variadic functions are always lowered to `global_asm!`, which uses the
+// default symbol names instead of those generated by rustc.
+#[naked_function::naked]
+pub unsafe extern "C" fn add(a: i32, args: ...) -> i32 {
+    asm!(return_value!(1));
+}
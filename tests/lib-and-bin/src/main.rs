@@ -0,0 +1,28 @@
+#![cfg_attr(target_arch = "mips", feature(asm_experimental_arch))]
+
+use lib_and_bin::return_value;
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add(a: i32, args: ...) -> i32 {
+    asm!(return_value!(2));
+}
+
+fn main() {
+    unsafe {
+        assert_eq!(lib_and_bin::add(0), 1);
+        assert_eq!(add(0), 2);
+    }
+}
+
+#[test]
+fn test_lib_and_bin() {
+    // If both functions had the same symbol then the linker would silently
+    // resolve both references to one of them.
+    let lib = lib_and_bin::add as *const () as usize;
+    let bin = add as *const () as usize;
+    assert_ne!(lib, bin);
+    unsafe {
+        assert_eq!(lib_and_bin::add(0), 1);
+        assert_eq!(add(0), 2);
+    }
+}
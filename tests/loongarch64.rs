@@ -0,0 +1,140 @@
+#![cfg(target_arch = "loongarch64")]
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+    asm!("add.w $a0, $a0, $a1", "ret");
+}
+
+#[test]
+fn test_add() {
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add_5(a: i32) -> i32 {
+    asm!(
+        "addi.w $a0, $a0, {}",
+        "ret",
+        const 5,
+    )
+}
+
+#[test]
+fn test_const() {
+    let ret = unsafe { add_5(3) };
+    assert_eq!(ret, 8);
+}
+
+extern "C" fn mutate_string(str: &mut String) {
+    assert_eq!(str, "hello");
+    *str = "world".into();
+}
+
+#[allow(improper_ctypes)]
+#[naked_function::naked]
+pub unsafe extern "C" fn call_sym(str: &mut String) -> i32 {
+    asm!(
+        "addi.d $sp, $sp, -16",
+        "st.d $ra, $sp, 8",
+        "bl {}",
+        "ld.d $ra, $sp, 8",
+        "addi.d $sp, $sp, 16",
+        "ret",
+        sym mutate_string
+    );
+}
+
+#[test]
+fn test_sym() {
+    let mut str = "hello".to_string();
+    unsafe {
+        call_sym(&mut str);
+    }
+    assert_eq!(str, "world");
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn sum(n: i32, args: ...) -> i32 {
+    asm!(
+        "move $t0, $a0",
+        "move $a0, $zero",
+        "beqz $t0, 2f",
+        "add.w $a0, $a0, $a1",
+        "addi.w $t0, $t0, -1",
+        "beqz $t0, 2f",
+        "add.w $a0, $a0, $a2",
+        "addi.w $t0, $t0, -1",
+        "beqz $t0, 2f",
+        "add.w $a0, $a0, $a3",
+        "addi.w $t0, $t0, -1",
+        "beqz $t0, 2f",
+        "add.w $a0, $a0, $a4",
+        "addi.w $t0, $t0, -1",
+        "beqz $t0, 2f",
+        "add.w $a0, $a0, $a5",
+        "2:",
+        "ret",
+    );
+}
+
+#[test]
+fn test_variadic() {
+    unsafe {
+        assert_eq!(sum(0), 0);
+        assert_eq!(sum(1, 5), 5);
+        assert_eq!(sum(3, 1, 2, 3), 6);
+        assert_eq!(sum(5, 1, 2, 3, 4, 5), 15);
+    }
+}
+
+#[naked_function::naked]
+#[export_name = "exported_symbol_name"]
+pub unsafe extern "C" fn export_name() -> i32 {
+    asm!("li.w $a0, 3", "ret");
+}
+
+#[test]
+fn test_export_name() {
+    extern "C" {
+        fn exported_symbol_name() -> i32;
+    }
+    let val = unsafe { exported_symbol_name() };
+    assert_eq!(val, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn mangled() -> i32 {
+    asm!("li.w $a0, 4", "ret");
+}
+
+mod scoped {
+    #[no_mangle]
+    fn mangled() -> i32 {
+        5
+    }
+}
+
+#[test]
+fn test_mangled() {
+    extern "C" {
+        fn mangled() -> i32;
+    }
+    let val = unsafe { mangled() };
+    assert_eq!(val, 5);
+}
+
+mod duplicate {
+    #[naked_function::naked]
+    pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+        asm!("add.w $a0, $a0, $a1", "addi.w $a0, $a0, 1", "ret");
+    }
+}
+
+#[test]
+fn test_duplicate_name() {
+    let ret = unsafe { duplicate::add(1, 2) };
+    assert_eq!(ret, 4);
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
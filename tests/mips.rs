@@ -0,0 +1,104 @@
+#![cfg(target_arch = "mips")]
+#![feature(asm_experimental_arch)]
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+    asm!("addu $2, $4, $5", "jr $31");
+}
+
+#[test]
+fn test_add() {
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add_5(a: i32) -> i32 {
+    asm!(
+        "addiu $2, $4, {}",
+        "jr $31",
+        const 5,
+    )
+}
+
+#[test]
+fn test_const() {
+    let ret = unsafe { add_5(3) };
+    assert_eq!(ret, 8);
+}
+
+// The addition is in the delay slot of the return, which is only executed
+// if the assembler doesn't insert a nop.
+#[naked_function::naked(mips::noreorder)]
+pub unsafe extern "C" fn add_noreorder(a: i32, b: i32) -> i32 {
+    asm!("jr $31", "addu $2, $4, $5");
+}
+
+#[test]
+fn test_noreorder() {
+    let ret = unsafe { add_noreorder(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+// Calling this requires a CPU with microMIPS support.
+#[naked_function::naked]
+#[instruction_set(mips::micromips)]
+pub unsafe extern "C" fn add_micromips(a: i32, b: i32) -> i32 {
+    asm!("addu $2, $4, $5", "jr $31");
+}
+
+#[test]
+fn test_micromips() {
+    assert_eq!(add_micromips as usize & 1, 1);
+}
+
+#[naked_function::naked]
+#[export_name = "exported_symbol_name"]
+pub unsafe extern "C" fn export_name() -> i32 {
+    asm!("li $2, 3", "jr $31");
+}
+
+#[test]
+fn test_export_name() {
+    extern "C" {
+        fn exported_symbol_name() -> i32;
+    }
+    let val = unsafe { exported_symbol_name() };
+    assert_eq!(val, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn mangled() -> i32 {
+    asm!("li $2, 4", "jr $31");
+}
+
+mod scoped {
+    #[no_mangle]
+    fn mangled() -> i32 {
+        5
+    }
+}
+
+#[test]
+fn test_mangled() {
+    extern "C" {
+        fn mangled() -> i32;
+    }
+    let val = unsafe { mangled() };
+    assert_eq!(val, 5);
+}
+
+mod duplicate {
+    #[naked_function::naked]
+    pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+        asm!("addu $2, $4, $5", "addiu $2, $2, 1", "jr $31");
+    }
+}
+
+#[test]
+fn test_duplicate_name() {
+    let ret = unsafe { duplicate::add(1, 2) };
+    assert_eq!(ret, 4);
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
@@ -0,0 +1,139 @@
+#![cfg(target_arch = "powerpc64")]
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+    asm!("add 3, 3, 4", "extsw 3, 3", "blr");
+}
+
+#[test]
+fn test_add() {
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+// On ELFv1 targets, function pointers point to the function descriptor.
+#[test]
+fn test_fn_ptr() {
+    let f: unsafe extern "C" fn(i32, i32) -> i32 = std::hint::black_box(add);
+    let ret = unsafe { f(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add_5(a: i32) -> i32 {
+    asm!(
+        "addi 3, 3, {}",
+        "extsw 3, 3",
+        "blr",
+        const 5,
+    )
+}
+
+#[test]
+fn test_const() {
+    let ret = unsafe { add_5(3) };
+    assert_eq!(ret, 8);
+}
+
+extern "C" fn mutate_string(str: &mut String) {
+    assert_eq!(str, "hello");
+    *str = "world".into();
+}
+
+#[allow(improper_ctypes)]
+#[naked_function::naked]
+pub unsafe extern "C" fn call_sym(str: &mut String) -> i32 {
+    asm!(
+        "mflr 0",
+        "std 0, 16(1)",
+        "stdu 1, -112(1)",
+        "bl {}",
+        "nop",
+        "addi 1, 1, 112",
+        "ld 0, 16(1)",
+        "mtlr 0",
+        "blr",
+        sym mutate_string
+    );
+}
+
+#[test]
+fn test_sym() {
+    let mut str = "hello".to_string();
+    unsafe {
+        call_sym(&mut str);
+    }
+    assert_eq!(str, "world");
+}
+
+static VALUE: i64 = 6;
+
+#[naked_function::naked(powerpc64::toc)]
+pub unsafe extern "C" fn value_addr() -> *const i64 {
+    asm!(
+        "addis 3, 2, {0}@toc@ha",
+        "addi 3, 3, {0}@toc@l",
+        "blr",
+        sym VALUE,
+    );
+}
+
+#[test]
+fn test_toc() {
+    let addr = unsafe { value_addr() };
+    assert_eq!(addr, &VALUE as *const i64);
+    let f: unsafe extern "C" fn() -> *const i64 = std::hint::black_box(value_addr);
+    let addr = unsafe { f() };
+    assert_eq!(unsafe { *addr }, 6);
+}
+
+#[naked_function::naked]
+#[export_name = "exported_symbol_name"]
+pub unsafe extern "C" fn export_name() -> i32 {
+    asm!("li 3, 3", "blr");
+}
+
+#[test]
+fn test_export_name() {
+    extern "C" {
+        fn exported_symbol_name() -> i32;
+    }
+    let val = unsafe { exported_symbol_name() };
+    assert_eq!(val, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn mangled() -> i32 {
+    asm!("li 3, 4", "blr");
+}
+
+mod scoped {
+    #[no_mangle]
+    fn mangled() -> i32 {
+        5
+    }
+}
+
+#[test]
+fn test_mangled() {
+    extern "C" {
+        fn mangled() -> i32;
+    }
+    let val = unsafe { mangled() };
+    assert_eq!(val, 5);
+}
+
+mod duplicate {
+    #[naked_function::naked]
+    pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+        asm!("add 3, 3, 4", "addi 3, 3, 1", "extsw 3, 3", "blr");
+    }
+}
+
+#[test]
+fn test_duplicate_name() {
+    let ret = unsafe { duplicate::add(1, 2) };
+    assert_eq!(ret, 4);
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
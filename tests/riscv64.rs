@@ -0,0 +1,143 @@
+#![cfg(target_arch = "riscv64")]
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+    asm!("addw a0, a0, a1", "ret");
+}
+
+#[test]
+fn test_add() {
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add_5(a: i32) -> i32 {
+    asm!(
+        "addiw a0, a0, {}",
+        "ret",
+        const 5,
+    )
+}
+
+#[test]
+fn test_const() {
+    let ret = unsafe { add_5(3) };
+    assert_eq!(ret, 8);
+}
+
+extern "C" fn mutate_string(str: &mut String) {
+    assert_eq!(str, "hello");
+    *str = "world".into();
+}
+
+#[allow(improper_ctypes)]
+#[naked_function::naked]
+pub unsafe extern "C" fn call_sym(str: &mut String) -> i32 {
+    asm!(
+        "addi sp, sp, -16",
+        "sd ra, 8(sp)",
+        "call {}",
+        "ld ra, 8(sp)",
+        "addi sp, sp, 16",
+        "ret",
+        sym mutate_string
+    );
+}
+
+#[test]
+fn test_sym() {
+    let mut str = "hello".to_string();
+    unsafe {
+        call_sym(&mut str);
+    }
+    assert_eq!(str, "world");
+}
+
+#[naked_function::naked]
+#[export_name = "exported_symbol_name"]
+pub unsafe extern "C" fn export_name() -> i32 {
+    asm!("li a0, 3", "ret");
+}
+
+#[test]
+fn test_export_name() {
+    extern "C" {
+        fn exported_symbol_name() -> i32;
+    }
+    let val = unsafe { exported_symbol_name() };
+    assert_eq!(val, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn mangled() -> i32 {
+    asm!("li a0, 4", "ret");
+}
+
+mod scoped {
+    #[no_mangle]
+    fn mangled() -> i32 {
+        5
+    }
+}
+
+#[test]
+fn test_mangled() {
+    extern "C" {
+        fn mangled() -> i32;
+    }
+    let val = unsafe { mangled() };
+    assert_eq!(val, 5);
+}
+
+mod duplicate {
+    #[naked_function::naked]
+    pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+        asm!("addw a0, a0, a1", "addiw a0, a0, 1", "ret");
+    }
+}
+
+#[test]
+fn test_duplicate_name() {
+    let ret = unsafe { duplicate::add(1, 2) };
+    assert_eq!(ret, 4);
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+#[naked_function::naked(riscv::norvc)]
+pub unsafe extern "C" fn add_norvc(a: i32, b: i32) -> i32 {
+    asm!("add a0, a0, a1", "ret");
+}
+
+#[naked_function::naked(riscv::rvc)]
+pub unsafe extern "C" fn add_rvc(a: i32, b: i32) -> i32 {
+    asm!("add a0, a0, a1", "ret");
+}
+
+// The low 2 bits of an instruction are 0b11 unless it is compressed.
+#[test]
+fn test_norvc() {
+    unsafe {
+        assert_eq!(add_norvc(1, 2), 3);
+        assert_eq!(add_rvc(1, 2), 3);
+        let insn = *(add_norvc as *const u16);
+        assert_eq!(insn & 3, 3);
+        let insn = *(add_rvc as *const u16);
+        assert_ne!(insn & 3, 3);
+    }
+}
+
+static VALUE: i32 = 6;
+
+#[naked_function::naked(riscv::norelax, riscv::norvc)]
+pub unsafe extern "C" fn value_addr() -> *const i32 {
+    asm!("lla a0, {}", "ret", sym VALUE);
+}
+
+#[test]
+fn test_norelax() {
+    let addr = unsafe { value_addr() };
+    assert_eq!(addr, &VALUE as *const i32);
+    assert_eq!(unsafe { *addr }, 6);
+}
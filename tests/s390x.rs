@@ -0,0 +1,144 @@
+#![cfg(target_arch = "s390x")]
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+    asm!("ar %r2, %r3", "lgfr %r2, %r2", "br %r14");
+}
+
+#[test]
+fn test_add() {
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+#[test]
+fn test_align() {
+    assert_eq!(add as *const () as usize % 8, 0);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add_5(a: i32) -> i32 {
+    asm!(
+        "ahi %r2, {}",
+        "lgfr %r2, %r2",
+        "br %r14",
+        const 5,
+    )
+}
+
+#[test]
+fn test_const() {
+    let ret = unsafe { add_5(3) };
+    assert_eq!(ret, 8);
+}
+
+extern "C" fn mutate_string(str: &mut String) {
+    assert_eq!(str, "hello");
+    *str = "world".into();
+}
+
+#[allow(improper_ctypes)]
+#[naked_function::naked]
+pub unsafe extern "C" fn call_sym(str: &mut String) -> i32 {
+    asm!(
+        "stmg %r14, %r15, 112(%r15)",
+        "aghi %r15, -160",
+        "brasl %r14, {}",
+        "lmg %r14, %r15, 272(%r15)",
+        "br %r14",
+        sym mutate_string
+    );
+}
+
+#[test]
+fn test_sym() {
+    let mut str = "hello".to_string();
+    unsafe {
+        call_sym(&mut str);
+    }
+    assert_eq!(str, "world");
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn sum(n: i32, args: ...) -> i32 {
+    asm!(
+        "lr %r0, %r2",
+        "lhi %r2, 0",
+        "ltr %r0, %r0",
+        "je 2f",
+        "ar %r2, %r3",
+        "ahi %r0, -1",
+        "je 2f",
+        "ar %r2, %r4",
+        "ahi %r0, -1",
+        "je 2f",
+        "ar %r2, %r5",
+        "ahi %r0, -1",
+        "je 2f",
+        "ar %r2, %r6",
+        "2:",
+        "lgfr %r2, %r2",
+        "br %r14",
+    );
+}
+
+#[test]
+fn test_variadic() {
+    unsafe {
+        assert_eq!(sum(0), 0);
+        assert_eq!(sum(1, 5), 5);
+        assert_eq!(sum(3, 1, 2, 3), 6);
+        assert_eq!(sum(4, 1, 2, 3, 4), 10);
+    }
+}
+
+#[naked_function::naked]
+#[export_name = "exported_symbol_name"]
+pub unsafe extern "C" fn export_name() -> i32 {
+    asm!("lghi %r2, 3", "br %r14");
+}
+
+#[test]
+fn test_export_name() {
+    extern "C" {
+        fn exported_symbol_name() -> i32;
+    }
+    let val = unsafe { exported_symbol_name() };
+    assert_eq!(val, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn mangled() -> i32 {
+    asm!("lghi %r2, 4", "br %r14");
+}
+
+mod scoped {
+    #[no_mangle]
+    fn mangled() -> i32 {
+        5
+    }
+}
+
+#[test]
+fn test_mangled() {
+    extern "C" {
+        fn mangled() -> i32;
+    }
+    let val = unsafe { mangled() };
+    assert_eq!(val, 5);
+}
+
+mod duplicate {
+    #[naked_function::naked]
+    pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+        asm!("ar %r2, %r3", "ahi %r2, 1", "lgfr %r2, %r2", "br %r14");
+    }
+}
+
+#[test]
+fn test_duplicate_name() {
+    let ret = unsafe { duplicate::add(1, 2) };
+    assert_eq!(ret, 4);
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
@@ -0,0 +1,94 @@
+#![cfg(all(
+    feature = "nightly",
+    target_os = "linux",
+    any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "riscv64",
+        target_arch = "loongarch64"
+    )
+))]
+#![feature(thread_local)]
+
+use std::ptr::addr_of_mut;
+
+#[thread_local]
+static mut COUNTER: u64 = 5;
+
+#[naked_function::naked]
+pub unsafe extern "C" fn counter_addr() -> *mut u64 {
+    asm!("{}", "ret", sym(tls) COUNTER);
+}
+
+#[cfg(not(target_arch = "x86"))]
+#[naked_function::naked]
+pub unsafe extern "C" fn counter_addr_ie() -> *mut u64 {
+    asm!("{}", "ret", sym(tls_ie) COUNTER);
+}
+
+#[cfg(target_arch = "x86")]
+#[naked_function::naked]
+pub unsafe extern "C" fn counter_addr_ie() -> *mut u64 {
+    asm!(
+        "push ebx",
+        "call __x86.get_pc_thunk.bx",
+        "add ebx, offset _GLOBAL_OFFSET_TABLE_",
+        "{}",
+        "pop ebx",
+        "ret",
+        sym(tls_ie) COUNTER,
+    );
+}
+
+#[cfg(target_arch = "x86_64")]
+#[naked_function::naked]
+pub unsafe extern "C" fn counter_addr_reg() -> *mut u64 {
+    asm!("{:rdx}", "mov rax, rdx", "ret", sym(tls) COUNTER);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[naked_function::naked]
+pub unsafe extern "C" fn counter_addr_ie_reg() -> *mut u64 {
+    asm!("{:rdx}", "mov rax, rdx", "ret", sym(tls_ie) COUNTER);
+}
+
+#[cfg(target_arch = "aarch64")]
+#[naked_function::naked]
+pub unsafe extern "C" fn counter_addr_reg() -> *mut u64 {
+    asm!("{:x17}", "mov x0, x17", "ret", sym(tls) COUNTER);
+}
+
+#[cfg(target_arch = "aarch64")]
+#[naked_function::naked]
+pub unsafe extern "C" fn counter_addr_ie_reg() -> *mut u64 {
+    asm!("{:x17}", "mov x0, x17", "ret", sym(tls_ie) COUNTER);
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+#[test]
+fn test_tls_reg() {
+    unsafe {
+        assert_eq!(counter_addr_reg(), addr_of_mut!(COUNTER));
+        assert_eq!(counter_addr_ie_reg(), addr_of_mut!(COUNTER));
+    }
+}
+
+#[test]
+fn test_tls() {
+    unsafe {
+        let ptr = counter_addr();
+        assert_eq!(ptr, addr_of_mut!(COUNTER));
+        assert_eq!(counter_addr_ie(), ptr);
+        *ptr = 6;
+        std::thread::spawn(|| {
+            let ptr = counter_addr();
+            assert_eq!(ptr, addr_of_mut!(COUNTER));
+            assert_eq!(counter_addr_ie(), ptr);
+            assert_eq!(*ptr, 5);
+        })
+        .join()
+        .unwrap();
+        assert_eq!(*ptr, 6);
+    }
+}
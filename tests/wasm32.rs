@@ -0,0 +1,118 @@
+#![cfg(target_arch = "wasm32")]
+#![feature(asm_experimental_arch)]
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+    asm!("local.get 0", "local.get 1", "i32.add");
+}
+
+#[test]
+fn test_add() {
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add_5(a: i32) -> i32 {
+    asm!(
+        "local.get 0",
+        "i32.const {}",
+        "i32.add",
+        const 5,
+    )
+}
+
+#[test]
+fn test_const() {
+    let ret = unsafe { add_5(3) };
+    assert_eq!(ret, 8);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add_u64(a: u64, b: u64) -> u64 {
+    asm!("local.get 0", "local.get 1", "i64.add");
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn mul_f64(a: f64, b: f64) -> f64 {
+    asm!("local.get 0", "local.get 1", "f64.mul");
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn load(ptr: *const i32) -> i32 {
+    asm!("local.get 0", "i32.load 0");
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn store(ptr: &mut i32, val: i32) {
+    asm!("local.get 0", "local.get 1", "i32.store 0");
+}
+
+#[test]
+fn test_functype() {
+    unsafe {
+        assert_eq!(add_u64(1 << 40, 2), (1 << 40) + 2);
+        assert_eq!(mul_f64(1.5, 2.0), 3.0);
+        let mut val = 6;
+        assert_eq!(load(&val), 6);
+        store(&mut val, 7);
+        assert_eq!(val, 7);
+    }
+}
+
+#[naked_function::naked]
+#[export_name = "exported_symbol_name"]
+pub unsafe extern "C" fn export_name() -> i32 {
+    asm!("i32.const 3");
+}
+
+#[test]
+fn test_export_name() {
+    extern "C" {
+        fn exported_symbol_name() -> i32;
+    }
+    let val = unsafe { exported_symbol_name() };
+    assert_eq!(val, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn mangled() -> i32 {
+    asm!("i32.const 4");
+}
+
+mod scoped {
+    #[no_mangle]
+    fn mangled() -> i32 {
+        5
+    }
+}
+
+#[test]
+fn test_mangled() {
+    extern "C" {
+        fn mangled() -> i32;
+    }
+    let val = unsafe { mangled() };
+    assert_eq!(val, 5);
+}
+
+mod duplicate {
+    #[naked_function::naked]
+    pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+        asm!(
+            "local.get 0",
+            "local.get 1",
+            "i32.add",
+            "i32.const 1",
+            "i32.add"
+        );
+    }
+}
+
+#[test]
+fn test_duplicate_name() {
+    let ret = unsafe { duplicate::add(1, 2) };
+    assert_eq!(ret, 4);
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
@@ -0,0 +1,149 @@
+#![cfg(target_arch = "x86")]
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+    asm!("mov eax, [esp + 4]", "add eax, [esp + 8]", "ret");
+}
+
+#[test]
+fn test_add() {
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add_5(a: i32) -> i32 {
+    asm!(
+        "mov eax, [esp + 4]",
+        "add eax, {}",
+        "ret",
+        const 5,
+    );
+}
+
+#[test]
+fn test_const() {
+    let ret = unsafe { add_5(3) };
+    assert_eq!(ret, 8);
+}
+
+#[naked_function::naked]
+pub unsafe extern "stdcall" fn add_stdcall(a: i32, b: i32) -> i32 {
+    asm!("mov eax, [esp + 4]", "add eax, [esp + 8]", "ret 8");
+}
+
+#[naked_function::naked]
+pub unsafe extern "fastcall" fn add_fastcall(a: i32, b: i32) -> i32 {
+    asm!("lea eax, [ecx + edx]", "ret");
+}
+
+#[naked_function::naked]
+pub unsafe extern "thiscall" fn add_thiscall(a: i32, b: i32) -> i32 {
+    asm!("mov eax, [esp + 4]", "add eax, ecx", "ret 4");
+}
+
+#[test]
+fn test_abi() {
+    let ret = unsafe { add_stdcall(1, 2) };
+    assert_eq!(ret, 3);
+    let ret = unsafe { add_fastcall(1, 2) };
+    assert_eq!(ret, 3);
+    let ret = unsafe { add_thiscall(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+// Lifetimes declared by higher-ranked binders in the argument types must be
+// kept when computing the size of the arguments for the decorated symbol.
+#[naked_function::naked]
+pub unsafe extern "stdcall" fn call_stdcall(
+    f: for<'a> extern "C" fn(&'a i32) -> i32,
+    x: &i32,
+) -> i32 {
+    asm!(
+        "push dword ptr [esp + 8]",
+        "call dword ptr [esp + 8]",
+        "add esp, 4",
+        "ret 8",
+    );
+}
+
+#[test]
+fn test_hrtb_arg() {
+    extern "C" fn double(x: &i32) -> i32 {
+        *x * 2
+    }
+    let ret = unsafe { call_stdcall(double, &3) };
+    assert_eq!(ret, 6);
+}
+
+// Native naked functions are decorated by rustc, so raw templates can be used.
+#[naked_function::naked]
+pub unsafe extern "stdcall" fn add_stdcall_raw(a: i32, b: i32) -> i32 {
+    asm!(
+        "mov eax, [esp + 4]",
+        "add eax, [esp + 8]",
+        "ret 8",
+        options(raw)
+    );
+}
+
+#[test]
+fn test_stdcall_raw() {
+    let ret = unsafe { add_stdcall_raw(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+#[naked_function::naked]
+#[export_name = "exported_stdcall_name"]
+pub unsafe extern "stdcall" fn export_name_stdcall(a: i32) -> i32 {
+    asm!("mov eax, [esp + 4]", "ret 4");
+}
+
+#[test]
+fn test_export_name_stdcall() {
+    extern "stdcall" {
+        fn exported_stdcall_name(a: i32) -> i32;
+    }
+    let val = unsafe { exported_stdcall_name(3) };
+    assert_eq!(val, 3);
+}
+
+static VALUE: i32 = 6;
+
+#[cfg(target_os = "linux")]
+#[naked_function::naked]
+pub unsafe extern "C" fn load_gotoff() -> i32 {
+    asm!(
+        "push ebx",
+        "call __x86.get_pc_thunk.bx",
+        "add ebx, offset _GLOBAL_OFFSET_TABLE_",
+        "mov eax, [ebx + {}]",
+        "pop ebx",
+        "ret",
+        sym(gotoff) VALUE,
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[naked_function::naked]
+pub unsafe extern "C" fn load_got() -> i32 {
+    asm!(
+        "push ebx",
+        "call __x86.get_pc_thunk.bx",
+        "add ebx, offset _GLOBAL_OFFSET_TABLE_",
+        "mov eax, [ebx + {}]",
+        "mov eax, [eax]",
+        "pop ebx",
+        "ret",
+        sym(got) VALUE,
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_pic() {
+    let val = unsafe { load_gotoff() };
+    assert_eq!(val, 6);
+    let val = unsafe { load_got() };
+    assert_eq!(val, 6);
+}
@@ -1,5 +1,4 @@
 #![cfg(target_arch = "x86_64")]
-#![feature(asm_const)]
 
 #[naked_function::naked]
 pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
@@ -47,6 +46,105 @@ fn test_sym() {
     assert_eq!(str, "world");
 }
 
+#[naked_function::naked]
+pub unsafe extern "C" fn add_qualified(a: i32, b: i32) -> i32 {
+    core::arch::asm!("lea eax, [edi + esi]", "ret");
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add_naked_asm(a: i32, b: i32) -> i32 {
+    naked_asm!("lea eax, [edi + esi]", "ret");
+}
+
+#[test]
+fn test_asm_paths() {
+    let ret = unsafe { add_qualified(1, 2) };
+    assert_eq!(ret, 3);
+    let ret = unsafe { add_naked_asm(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add_att(a: i32, b: i32) -> i32 {
+    asm!(
+        "leal (%rdi, %rsi), %eax",
+        "ret",
+        options(att_syntax, noreturn)
+    );
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn add_noreturn(a: i32, b: i32) -> i32 {
+    asm!("lea eax, [edi + esi]", "ret", options(noreturn));
+}
+
+#[test]
+fn test_options() {
+    let ret = unsafe { add_att(1, 2) };
+    assert_eq!(ret, 3);
+    let ret = unsafe { add_noreturn(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "sysv64" fn add_sysv64(a: i32, b: i32) -> i32 {
+    asm!("lea eax, [edi + esi]", "ret");
+}
+
+#[naked_function::naked]
+pub unsafe extern "win64" fn add_win64(a: i32, b: i32) -> i32 {
+    asm!("lea eax, [ecx + edx]", "ret");
+}
+
+#[naked_function::naked]
+pub unsafe extern "efiapi" fn add_efiapi(a: i32, b: i32) -> i32 {
+    asm!("lea eax, [ecx + edx]", "ret");
+}
+
+#[test]
+fn test_abi() {
+    let ret = unsafe { add_sysv64(1, 2) };
+    assert_eq!(ret, 3);
+    let ret = unsafe { add_win64(1, 2) };
+    assert_eq!(ret, 3);
+    let ret = unsafe { add_efiapi(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+#[naked_function::naked]
+pub unsafe extern "C" fn sum(n: i32, args: ...) -> i32 {
+    asm!(
+        "xor eax, eax",
+        "test edi, edi",
+        "jz 2f",
+        "add eax, esi",
+        "dec edi",
+        "jz 2f",
+        "add eax, edx",
+        "dec edi",
+        "jz 2f",
+        "add eax, ecx",
+        "dec edi",
+        "jz 2f",
+        "add eax, r8d",
+        "dec edi",
+        "jz 2f",
+        "add eax, r9d",
+        "2:",
+        "ret",
+    );
+}
+
+#[test]
+fn test_variadic() {
+    unsafe {
+        assert_eq!(sum(0), 0);
+        assert_eq!(sum(1, 5), 5);
+        assert_eq!(sum(3, 1, 2, 3), 6);
+        assert_eq!(sum(5, 1, 2, 3, 4, 5), 15);
+    }
+}
+
 #[naked_function::naked]
 #[export_name = "exported_symbol_name"]
 pub unsafe extern "C" fn export_name() -> i32 {
@@ -62,6 +160,22 @@ fn test_export_name() {
     assert_eq!(val, 3);
 }
 
+#[naked_function::naked]
+#[unsafe(export_name = "unsafe_exported_symbol_name")]
+#[unsafe(link_section = ".text.unsafe_exported_symbol_name")]
+pub unsafe extern "C" fn unsafe_export_name() -> i32 {
+    asm!("mov rax, 6", "ret");
+}
+
+#[test]
+fn test_unsafe_export_name() {
+    extern "C" {
+        fn unsafe_exported_symbol_name() -> i32;
+    }
+    let val = unsafe { unsafe_exported_symbol_name() };
+    assert_eq!(val, 6);
+}
+
 #[naked_function::naked]
 pub unsafe extern "C" fn mangled() -> i32 {
     asm!("mov rax, 4", "ret");
@@ -82,3 +196,236 @@ fn test_mangled() {
     let val = unsafe { mangled() };
     assert_eq!(val, 5);
 }
+
+mod duplicate {
+    #[naked_function::naked]
+    pub unsafe extern "C" fn add(a: i32, b: i32) -> i32 {
+        asm!("lea eax, [edi + esi + 1]", "ret");
+    }
+}
+
+#[test]
+fn test_duplicate_name() {
+    let ret = unsafe { duplicate::add(1, 2) };
+    assert_eq!(ret, 4);
+    let ret = unsafe { add(1, 2) };
+    assert_eq!(ret, 3);
+}
+
+#[cfg(unix)]
+extern "C" {
+    fn abs(x: i32) -> i32;
+}
+
+// `abs` is defined in the C library, which is usually a shared library.
+#[cfg(unix)]
+#[naked_function::naked]
+pub unsafe extern "C" fn call_abs(x: i32) -> i32 {
+    asm!(
+        "push rax",
+        "call {}",
+        "pop rcx",
+        "ret",
+        sym(call) abs,
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sym_call() {
+    let ret = unsafe { call_abs(-3) };
+    assert_eq!(ret, 3);
+}
+
+#[cfg(windows)]
+#[naked_function::naked(unwind_tables)]
+pub unsafe extern "C-unwind" fn trampoline(f: extern "C-unwind" fn()) {
+    asm!(
+        "push rbp",
+        ".seh_pushreg rbp",
+        "sub rsp, 32",
+        ".seh_stackalloc 32",
+        ".seh_endprologue",
+        "call rcx",
+        "add rsp, 32",
+        "pop rbp",
+        "ret",
+    );
+}
+
+#[cfg(windows)]
+#[test]
+fn test_seh_unwind() {
+    extern "C-unwind" fn panics() {
+        panic!("unwinding through a naked function");
+    }
+    let result = std::panic::catch_unwind(|| unsafe { trampoline(panics) });
+    assert!(result.is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[naked_function::naked]
+pub unsafe extern "C-unwind" fn cfi_trampoline(f: extern "C-unwind" fn()) {
+    asm!(
+        "push rbp",
+        ".cfi_adjust_cfa_offset 8",
+        ".cfi_offset rbp, -16",
+        "call rdi",
+        "pop rbp",
+        ".cfi_adjust_cfa_offset -8",
+        ".cfi_restore rbp",
+        "ret",
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[naked_function::naked(unwind_tables)]
+pub unsafe extern "C-unwind" fn explicit_cfi_trampoline(f: extern "C-unwind" fn()) {
+    asm!(
+        "push rbp",
+        ".cfi_adjust_cfa_offset 8",
+        ".cfi_offset rbp, -16",
+        "call rdi",
+        "pop rbp",
+        ".cfi_adjust_cfa_offset -8",
+        ".cfi_restore rbp",
+        "ret",
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[naked_function::naked(no_unwind_tables)]
+pub unsafe extern "C-unwind" fn manual_cfi_trampoline(f: extern "C-unwind" fn()) {
+    asm!(
+        ".cfi_startproc",
+        "push rbp",
+        ".cfi_def_cfa_offset 16",
+        ".cfi_offset rbp, -16",
+        "call rdi",
+        "pop rbp",
+        ".cfi_def_cfa_offset 8",
+        "ret",
+        ".cfi_endproc",
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_cfi_unwind() {
+    extern "C-unwind" fn panics() {
+        panic!("unwinding through a naked function");
+    }
+    let result = std::panic::catch_unwind(|| unsafe { cfi_trampoline(panics) });
+    assert!(result.is_err());
+    let result = std::panic::catch_unwind(|| unsafe { explicit_cfi_trampoline(panics) });
+    assert!(result.is_err());
+    let result = std::panic::catch_unwind(|| unsafe { manual_cfi_trampoline(panics) });
+    assert!(result.is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[naked_function::naked(entry_point)]
+pub unsafe extern "C" fn entry_point(f: extern "C" fn()) {
+    asm!(
+        "push rax",
+        ".cfi_adjust_cfa_offset 8",
+        "call rdi",
+        "pop rax",
+        ".cfi_adjust_cfa_offset -8",
+        "ret",
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_entry_point() {
+    use std::ffi::c_void;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    extern "C" {
+        fn _Unwind_Backtrace(
+            trace: extern "C" fn(*mut c_void, *mut c_void) -> i32,
+            arg: *mut c_void,
+        ) -> i32;
+        fn _Unwind_GetIP(ctx: *mut c_void) -> usize;
+    }
+
+    static LAST_IP: AtomicUsize = AtomicUsize::new(0);
+    extern "C" fn trace(ctx: *mut c_void, _arg: *mut c_void) -> i32 {
+        // The outermost frame is reported with a null return address.
+        let ip = unsafe { _Unwind_GetIP(ctx) };
+        if ip != 0 {
+            LAST_IP.store(ip, Ordering::Relaxed);
+        }
+        0
+    }
+    extern "C" fn backtrace() {
+        unsafe {
+            _Unwind_Backtrace(trace, std::ptr::null_mut());
+        }
+    }
+
+    // The backtrace should stop at the return address in the entry point.
+    unsafe { entry_point(backtrace) };
+    let entry = entry_point as *const () as usize;
+    let ip = LAST_IP.load(Ordering::Relaxed);
+    assert!(
+        (entry..entry + 16).contains(&ip),
+        "backtrace didn't stop at the entry point"
+    );
+}
+
+#[cfg(all(target_os = "linux", proc_macro_span))]
+#[naked_function::naked(debug_lines)]
+pub unsafe extern "C" fn debug_lines(a: i32) -> i32 {
+    asm!(
+        "mov eax, edi",
+        concat!("add eax, ", "{}"),
+        "ret",
+        const 5,
+    );
+}
+
+#[cfg(all(target_os = "linux", proc_macro_span))]
+#[naked_function::naked(debug_lines)]
+pub unsafe extern "C" fn debug_lines_call(f: extern "C" fn()) {
+    asm!(
+        // Keep the stack aligned for the call.
+        "push rax",
+        "mov rax, rdi",
+        "call rax",
+        "pop rax",
+        "ret",
+    );
+}
+
+#[cfg(all(target_os = "linux", proc_macro_span))]
+#[test]
+fn test_debug_lines() {
+    use std::backtrace::Backtrace;
+    use std::sync::Mutex;
+
+    static BACKTRACE: Mutex<String> = Mutex::new(String::new());
+    extern "C" fn capture() {
+        *BACKTRACE.lock().unwrap() = Backtrace::force_capture().to_string();
+    }
+
+    let ret = unsafe { debug_lines(1) };
+    assert_eq!(ret, 6);
+
+    // The return address of the callback is attributed to the line of the
+    // `call` template.
+    unsafe { debug_lines_call(capture) };
+    let line = include_str!("x86_64.rs")
+        .lines()
+        .position(|line| line.trim() == "\"call rax\",")
+        .unwrap()
+        + 1;
+    let backtrace = BACKTRACE.lock().unwrap();
+    assert!(
+        backtrace.contains(&format!("x86_64.rs:{}:9", line)),
+        "line {} not found in backtrace:\n{}",
+        line,
+        backtrace
+    );
+}